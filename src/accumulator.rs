@@ -0,0 +1,202 @@
+use image::{ImageBuffer, Rgb, RgbImage};
+
+use crate::{framebuffer::FrameBuffer, utils::luminance};
+
+// Pixels darker than this count as this bright when measuring relative noise,
+// so near-black pixels with a stray bright sample don't dominate
+const NOISE_FLOOR: f64 = 0.05;
+
+// Samples taken for one pixel in one pass
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PixelSamples {
+    pub count: u32,
+    pub sum: [f64; 3],
+    pub luminance_sum: f64,
+    pub luminance_square_sum: f64,
+}
+
+impl PixelSamples {
+    pub fn add(&mut self, color: Rgb<f64>) {
+        let l: f64 = luminance(color.0);
+        self.count += 1;
+        for channel in 0..3 {
+            self.sum[channel] += color[channel];
+        }
+        self.luminance_sum += l;
+        self.luminance_square_sum += l*l;
+    }
+}
+
+// Running sums of every sample taken for each pixel over any number of passes,
+// from which the mean color and how noisy it still is can be read.
+// Coordinates start from the top left like the framebuffer.
+#[derive(Clone)]
+pub struct Accumulator {
+    width: u32,
+    height: u32,
+    pixels: Vec<PixelSamples>,
+}
+
+impl Accumulator {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![PixelSamples::default(); (width * height) as usize]
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> &PixelSamples {
+        &self.pixels[(y * self.width + x) as usize]
+    }
+
+    pub fn add(&mut self, x: u32, y: u32, samples: &PixelSamples) {
+        let pixel: &mut PixelSamples = &mut self.pixels[(y * self.width + x) as usize];
+        pixel.count += samples.count;
+        for channel in 0..3 {
+            pixel.sum[channel] += samples.sum[channel];
+        }
+        pixel.luminance_sum += samples.luminance_sum;
+        pixel.luminance_square_sum += samples.luminance_square_sum;
+    }
+
+    // Linear color averaged over the pixel's samples
+    pub fn mean(&self, x: u32, y: u32) -> Rgb<f64> {
+        let pixel: &PixelSamples = self.get_pixel(x, y);
+        if pixel.count == 0 {
+            return Rgb::from([0.0, 0.0, 0.0]);
+        }
+        Rgb::from(pixel.sum.map(|sum| sum * (1.0 / pixel.count as f64)))
+    }
+
+    // Standard error of the pixel's mean luminance relative to the luminance,
+    // None until the pixel has two samples to estimate it from
+    pub fn relative_error(&self, x: u32, y: u32) -> Option<f64> {
+        let pixel: &PixelSamples = self.get_pixel(x, y);
+        if pixel.count < 2 {
+            return None;
+        }
+
+        let n: f64 = pixel.count as f64;
+        let mean: f64 = pixel.luminance_sum / n;
+        let variance: f64 = ((pixel.luminance_square_sum - n*mean*mean) / (n - 1.0)).max(0.0);
+        Some((variance / n).sqrt() / mean.max(NOISE_FLOOR))
+    }
+
+    // Average relative error over the image, None until every pixel has one
+    pub fn noise(&self) -> Option<f64> {
+        let mut total: f64 = 0.0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                total += self.relative_error(x, y)?;
+            }
+        }
+        Some(total / (self.width * self.height) as f64)
+    }
+
+    pub fn average_count(&self) -> f64 {
+        let total: u64 = self.pixels.iter().map(|pixel| pixel.count as u64).sum();
+        total as f64 / self.pixels.len() as f64
+    }
+
+    // Image of how many samples each pixel took, from black for the fewest
+    // through red and yellow to white for the most
+    pub fn sample_heatmap(&self) -> RgbImage {
+        let min: u32 = self.pixels.iter().map(|pixel| pixel.count).min().unwrap_or(0);
+        let max: u32 = self.pixels.iter().map(|pixel| pixel.count).max().unwrap_or(0);
+
+        ImageBuffer::from_fn(self.width, self.height, |x, y| {
+            let t: f64 = if max > min { (self.get_pixel(x, y).count - min) as f64 / (max - min) as f64 } else { 0.0 };
+            heatmap_color(t)
+        })
+    }
+
+    pub fn to_framebuffer(&self) -> FrameBuffer {
+        let mut framebuffer: FrameBuffer = FrameBuffer::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                framebuffer.put_pixel(x, y, self.mean(x, y));
+            }
+        }
+        framebuffer
+    }
+}
+
+// t in [0, 1] along black, red, yellow and white
+fn heatmap_color(t: f64) -> Rgb<u8> {
+    const STOPS: [[f64; 3]; 4] = [[0.0, 0.0, 0.0], [0.8, 0.0, 0.0], [1.0, 0.8, 0.0], [1.0, 1.0, 1.0]];
+
+    let position: f64 = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f64;
+    let index: usize = (position as usize).min(STOPS.len() - 2);
+    let f: f64 = position - index as f64;
+    Rgb::from([0, 1, 2].map(|channel| {
+        let value: f64 = STOPS[index][channel]*(1.0 - f) + STOPS[index + 1][channel]*f;
+        (255.0*value).round() as u8
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage};
+
+    use super::{Accumulator, PixelSamples, heatmap_color};
+
+    fn samples(colors: &[f64]) -> PixelSamples {
+        let mut samples: PixelSamples = PixelSamples::default();
+        for &color in colors {
+            samples.add(Rgb::from([color, color, color]));
+        }
+        samples
+    }
+
+    #[test]
+    fn relative_error_needs_two_samples() {
+        let mut accumulator: Accumulator = Accumulator::new(2, 1);
+        accumulator.add(0, 0, &samples(&[0.5]));
+        accumulator.add(1, 0, &samples(&[0.5, 0.5]));
+
+        assert_eq!(accumulator.relative_error(0, 0), None);
+        assert_eq!(accumulator.relative_error(1, 0), Some(0.0));
+        assert_eq!(accumulator.noise(), None);
+    }
+
+    #[test]
+    fn relative_error_is_the_standard_error_over_the_mean() {
+        let mut accumulator: Accumulator = Accumulator::new(1, 1);
+        accumulator.add(0, 0, &samples(&[0.0, 1.0]));
+
+        // Sample variance 0.5, standard error 0.5, mean 0.5
+        assert!((accumulator.relative_error(0, 0).unwrap() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn heatmap_spans_the_fewest_to_the_most_samples() {
+        let mut accumulator: Accumulator = Accumulator::new(3, 1);
+        accumulator.add(0, 0, &samples(&[0.5; 4]));
+        accumulator.add(1, 0, &samples(&[0.5; 10]));
+        accumulator.add(2, 0, &samples(&[0.5; 16]));
+
+        let heatmap: RgbImage = accumulator.sample_heatmap();
+        assert_eq!(*heatmap.get_pixel(0, 0), Rgb::from([0, 0, 0]));
+        assert_eq!(*heatmap.get_pixel(1, 0), heatmap_color(0.5));
+        assert_eq!(*heatmap.get_pixel(2, 0), Rgb::from([255, 255, 255]));
+    }
+
+    #[test]
+    fn heatmap_of_equal_counts_is_black() {
+        let mut accumulator: Accumulator = Accumulator::new(2, 1);
+        accumulator.add(0, 0, &samples(&[0.5; 8]));
+        accumulator.add(1, 0, &samples(&[0.5; 8]));
+
+        let heatmap: RgbImage = accumulator.sample_heatmap();
+        assert!(heatmap.pixels().all(|pixel| *pixel == Rgb::from([0, 0, 0])));
+    }
+}
\ No newline at end of file
@@ -1,26 +1,128 @@
 use clap::Parser;
 
+use rust_ray_tracer::{vec3::{Point3, Vec3}, samplers::sampler::SamplerKind};
+
 #[derive(Parser, Debug)]
 pub struct Args {
-    /// Name of the output image file
+    /// Name of the output image file, .exr, .hdr and .pfm files keep the linear high dynamic range values
     #[arg(short, long, default_value_t = format!("out.png"))]
     pub out_file: String,
 
-    /// Width of the image
-    #[arg(short, long, default_value_t = 400)]
-    pub image_width: u32,
+    /// Scene description file (JSON) to render instead of the default scene
+    #[arg(short, long)]
+    pub scene: Option<String>,
+
+    /// Width of the image [default: 400, or the scene's image_width]
+    #[arg(short, long)]
+    pub image_width: Option<u32>,
+
+    /// Numerator of the aspect ratio [default: 16, or the scene's aspect_ratio]
+    #[arg(short, long, requires = "denominator_ar")]
+    pub numerator_ar: Option<f64>,
+
+    /// Denominator of the aspect ratio [default: 9, or the scene's aspect_ratio]
+    #[arg(short, long, requires = "numerator_ar")]
+    pub denominator_ar: Option<f64>,
+
+    /// Number of rays traced for each pixel [default: 100, or the scene's samples_per_pixel]
+    #[arg(long)]
+    pub samples_per_pixel: Option<u32>,
+
+    /// Maximum number of bounces for each ray [default: 50, or the scene's max_depth]
+    #[arg(long)]
+    pub max_depth: Option<u32>,
+
+    /// Gamma used to encode the output image [default: 2, or the scene's gamma]
+    #[arg(long)]
+    pub gamma: Option<f64>,
+
+    /// Constant background color as r,g,b, e.g. 0,0,0 for scenes lit only by their lights [default: sky gradient, or the scene's background]
+    #[arg(long)]
+    pub background: Option<Vec3>,
+
+    /// Position of the camera as x,y,z [default: 0,0,0, or the scene's camera]
+    #[arg(long, allow_hyphen_values = true)]
+    pub look_from: Option<Point3>,
+
+    /// Point the camera looks at as x,y,z [default: 0,0,-1, or the scene's camera]
+    #[arg(long, allow_hyphen_values = true)]
+    pub look_at: Option<Point3>,
+
+    /// Up direction of the camera as x,y,z [default: 0,1,0, or the scene's camera]
+    #[arg(long, allow_hyphen_values = true)]
+    pub vup: Option<Vec3>,
+
+    /// Vertical field of view in degrees [default: 90, or the scene's camera]
+    #[arg(long)]
+    pub vfov: Option<f64>,
+
+    /// Diameter of the camera lens, zero disables depth of field [default: 0, or the scene's camera]
+    #[arg(long)]
+    pub aperture: Option<f64>,
 
-    /// Numerator of the aspect ratio
-    #[arg(short, long, default_value_t = 16.0)]
-    pub numerator_ar: f64,
+    /// Distance to the plane in perfect focus [default: distance from look-from to look-at]
+    #[arg(long)]
+    pub focus_dist: Option<f64>,
 
-    /// Denominator of the aspect ratio
-    #[arg(short, long, default_value_t = 9.0)]
-    pub denominator_ar: f64,
+    /// Time the camera shutter opens, moving objects are blurred until it closes [default: 0, or the scene's camera]
+    #[arg(long, allow_hyphen_values = true)]
+    pub shutter_open: Option<f64>,
+
+    /// Time the camera shutter closes [default: 0, or the scene's camera]
+    #[arg(long, allow_hyphen_values = true)]
+    pub shutter_close: Option<f64>,
+
+    /// Seed of the random numbers, renders with the same seed and settings are identical whatever the number of threads [default: 0, or the scene's seed]
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Where the sample values for the pixel, lens, time and bounces come from: independent, stratified, halton, sobol or blue-noise. The last four leave less noise for the same number of samples [default: independent]
+    #[arg(long)]
+    pub sampler: Option<SamplerKind>,
 
     /// Run the render multithreaded
     #[arg(short, long, default_value_t = false)]
-    pub multithread: bool
+    pub multithread: bool,
+
+    /// Number of render threads, implies --multithread [default: number of available cores]
+    #[arg(short, long)]
+    pub threads: Option<u32>,
+
+    /// Size in pixels of the square tiles handed out to render threads [default: 16, or the scene's tile_size]
+    #[arg(long)]
+    pub tile_size: Option<u32>,
+
+    /// Render in passes, writing the image so far to the output file as it goes. Stops at --samples-per-pixel, --time-limit or --noise-threshold, whichever comes first
+    #[arg(long, default_value_t = false)]
+    pub progressive: bool,
+
+    /// Samples added to every pixel in each pass of a progressive render [default: 4]
+    #[arg(long)]
+    pub samples_per_pass: Option<u32>,
+
+    /// Seconds a progressive render may take, it stops after the pass that goes over. Implies --progressive
+    #[arg(long)]
+    pub time_limit: Option<f64>,
+
+    /// Average relative error of the pixels at which a progressive render stops, e.g. 0.01. Implies --progressive
+    #[arg(long)]
+    pub noise_threshold: Option<f64>,
+
+    /// Seconds between writes of the image during a progressive render
+    #[arg(long, default_value_t = 10.0)]
+    pub write_interval: f64,
+
+    /// Adaptive sampling: keep sampling a pixel, up to --samples-per-pixel, until the standard error of its mean is below this fraction of its brightness, e.g. 0.02
+    #[arg(long)]
+    pub adaptive_threshold: Option<f64>,
+
+    /// Samples every pixel gets before adaptive sampling judges how noisy it is [default: 16]
+    #[arg(long)]
+    pub adaptive_min_samples: Option<u32>,
+
+    /// Also write an image of how many samples each pixel took, to see where adaptive sampling spent them
+    #[arg(long)]
+    pub spp_heatmap: Option<String>
 }
 
 pub fn parse_command_line_args() -> Args {
@@ -0,0 +1,24 @@
+use crate::ray::Ray;
+use crate::rgb_wrapper::RgbWrapper;
+use crate::vec3::Vec3;
+
+// What a ray that escapes the scene sees. Backgrounds that light the scene
+// unevenly, like environment maps, can also be sampled as lights.
+pub trait Background {
+    fn color(&self, ray: &Ray) -> RgbWrapper;
+
+    // Whether pdf_value and random importance sample the background
+    fn importance_sampled(&self) -> bool {
+        false
+    }
+
+    // Probability density, per unit solid angle, of random() picking the direction
+    fn pdf_value(&self, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    // Direction picked by u, a point in the unit square
+    fn random(&self, _u: [f64; 2]) -> Vec3 {
+        Vec3::new(0.0, 1.0, 0.0)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,27 @@
+use image::Rgb;
+
+use crate::{
+    ray::Ray,
+    rgb_wrapper::RgbWrapper};
+
+use super::background::Background;
+
+// The same color in every direction, black for scenes lit only by their lights
+#[derive(Clone, Copy)]
+pub struct Constant {
+    pub color: RgbWrapper,
+}
+
+impl Constant {
+    pub fn new(color: Rgb<f64>) -> Self {
+        Self {
+            color: RgbWrapper(color)
+        }
+    }
+}
+
+impl Background for Constant {
+    fn color(&self, _ray: &Ray) -> RgbWrapper {
+        self.color
+    }
+}
\ No newline at end of file
@@ -0,0 +1,216 @@
+use std::{f64::consts::PI, fs::File, io::BufReader, path::Path};
+
+use image::{codecs::hdr::HdrDecoder, DynamicImage, ImageResult, Rgb32FImage};
+
+use crate::{
+    ray::Ray,
+    rgb_wrapper::RgbWrapper,
+    utils::luminance,
+    samplers::hash::ONE_MINUS_EPSILON,
+    textures::image_texture::srgb_to_linear,
+    vec3::Vec3};
+
+use super::background::Background;
+
+// Equirectangular (latitude-longitude) image around the scene, usually a high
+// dynamic range .hdr or .exr photo of real surroundings. The top row of the
+// image is straight up and the center looks down -Z.
+//
+// Pixels are importance sampled by their luminance, so bright features like
+// the sun light the scene without needing to be hit by chance.
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    // Linear colors, row by row from the top
+    pixels: Vec<[f64; 3]>,
+    // Rotation about the Y axis in radians
+    rotation: f64,
+    intensity: f64,
+    // Chance of sampling each row, then each pixel within its row
+    rows: Distribution,
+    columns: Vec<Distribution>,
+}
+
+impl EnvironmentMap {
+    // rotation is counter-clockwise about the Y axis in degrees, intensity scales the image
+    pub fn load(file: &Path, rotation: f64, intensity: f64) -> ImageResult<Self> {
+        // Radiance files are tone mapped to 8 bits when opened like other images
+        if file.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("hdr")) {
+            let decoder: HdrDecoder<BufReader<File>> = HdrDecoder::new(BufReader::new(File::open(file)?))?;
+            let (width, height) = (decoder.metadata().width as usize, decoder.metadata().height as usize);
+            let pixels: Vec<[f64; 3]> = decoder.read_image_hdr()?.iter()
+                .map(|p| [p[0] as f64, p[1] as f64, p[2] as f64])
+                .collect();
+            return Ok(Self::new(width, height, pixels, rotation, intensity));
+        }
+
+        let image: DynamicImage = image::open(file)?;
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let is_linear: bool = matches!(image, DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_));
+
+        let pixels: Vec<[f64; 3]> = if is_linear {
+            let image: Rgb32FImage = image.into_rgb32f();
+            image.pixels().map(|p| [p[0] as f64, p[1] as f64, p[2] as f64]).collect()
+        } else {
+            image.to_rgb8().pixels().map(|p| [srgb_to_linear(p[0]), srgb_to_linear(p[1]), srgb_to_linear(p[2])]).collect()
+        };
+
+        Ok(Self::new(width, height, pixels, rotation, intensity))
+    }
+
+    pub fn new(width: usize, height: usize, pixels: Vec<[f64; 3]>, rotation: f64, intensity: f64) -> Self {
+        let mut columns: Vec<Distribution> = Vec::with_capacity(height);
+        for row in 0..height {
+            // Rows near the poles cover less of the sphere
+            let sin_theta: f64 = (PI * (row as f64 + 0.5) / height as f64).sin();
+            let weights: Vec<f64> = pixels[row*width..(row + 1)*width].iter()
+                .map(|p| luminance(*p) * sin_theta)
+                .collect();
+            columns.push(Distribution::new(weights));
+        }
+        let rows: Distribution = Distribution::new(columns.iter().map(|c| c.total).collect());
+
+        Self {
+            width,
+            height,
+            pixels,
+            rotation: rotation.to_radians(),
+            intensity,
+            rows,
+            columns
+        }
+    }
+
+    // Image coordinates in [0, 1] for a direction, with v = 0 straight up
+    fn direction_to_uv(&self, direction: &Vec3) -> (f64, f64) {
+        let d: Vec3 = direction.unit_vector();
+        let phi: f64 = d.x().atan2(-d.z()) + self.rotation;
+        let u: f64 = (phi / (2.0*PI) + 0.5).rem_euclid(1.0);
+        let v: f64 = d.y().clamp(-1.0, 1.0).acos() / PI;
+        (u, v)
+    }
+
+    fn uv_to_direction(&self, u: f64, v: f64) -> Vec3 {
+        let phi: f64 = (u - 0.5)*2.0*PI - self.rotation;
+        let theta: f64 = v*PI;
+        Vec3::new(theta.sin()*phi.sin(), theta.cos(), -theta.sin()*phi.cos())
+    }
+
+    fn pixel_index(&self, u: f64, v: f64) -> (usize, usize) {
+        let column: usize = ((u * self.width as f64) as usize).min(self.width - 1);
+        let row: usize = ((v * self.height as f64) as usize).min(self.height - 1);
+        (row, column)
+    }
+}
+
+impl Background for EnvironmentMap {
+    fn color(&self, ray: &Ray) -> RgbWrapper {
+        let (u, v) = self.direction_to_uv(&ray.direction());
+        let (row, column) = self.pixel_index(u, v);
+        let pixel: [f64; 3] = self.pixels[row*self.width + column];
+        RgbWrapper(pixel.into()) * self.intensity
+    }
+
+    fn importance_sampled(&self) -> bool {
+        self.rows.total > 0.0
+    }
+
+    fn pdf_value(&self, direction: &Vec3) -> f64 {
+        let (u, v) = self.direction_to_uv(direction);
+        let sin_theta: f64 = (v*PI).sin();
+        if sin_theta <= 0.0 || self.rows.total <= 0.0 {
+            return 0.0;
+        }
+
+        // Density over the image, then over the sphere it wraps
+        let (row, column) = self.pixel_index(u, v);
+        let uv_pdf: f64 = self.rows.probability(row) * self.columns[row].probability(column)
+            * (self.width * self.height) as f64;
+        uv_pdf / (2.0*PI*PI*sin_theta)
+    }
+
+    // u picks the row and then the pixel in it, what is left of each dimension
+    // picks the point within the pixel
+    fn random(&self, u: [f64; 2]) -> Vec3 {
+        let (row, v_offset) = self.rows.sample(u[1]);
+        let (column, u_offset) = self.columns[row].sample(u[0]);
+
+        let u: f64 = (column as f64 + u_offset) / self.width as f64;
+        let v: f64 = (row as f64 + v_offset) / self.height as f64;
+        self.uv_to_direction(u, v)
+    }
+}
+
+// Discrete distribution proportional to a list of non-negative weights
+struct Distribution {
+    weights: Vec<f64>,
+    // Running sums of the weights, cdf[i] is the sum of the weights before i
+    cdf: Vec<f64>,
+    total: f64,
+}
+
+impl Distribution {
+    fn new(weights: Vec<f64>) -> Self {
+        let mut cdf: Vec<f64> = Vec::with_capacity(weights.len() + 1);
+        let mut total: f64 = 0.0;
+        cdf.push(0.0);
+        for weight in &weights {
+            total += weight.max(0.0);
+            cdf.push(total);
+        }
+
+        Self {
+            weights,
+            cdf,
+            total
+        }
+    }
+
+    fn probability(&self, index: usize) -> f64 {
+        if self.total > 0.0 { self.weights[index].max(0.0) / self.total } else { 0.0 }
+    }
+
+    // Index picked by xi in [0, 1), along with where xi fell between the start
+    // and end of the index's share of [0, 1), itself in [0, 1)
+    fn sample(&self, xi: f64) -> (usize, f64) {
+        let target: f64 = xi * self.total;
+        let index: usize = self.cdf.partition_point(|&c| c <= target).saturating_sub(1).min(self.weights.len() - 1);
+        let weight: f64 = self.weights[index].max(0.0);
+        let offset: f64 = if weight > 0.0 { (target - self.cdf[index]) / weight } else { 0.5 };
+        (index, offset.clamp(0.0, ONE_MINUS_EPSILON))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pdfs::{background_pdf::BackgroundPdf, pdf::tests::{integrate, assert_generate_matches_value}},
+        utils::new_rng};
+
+    use super::EnvironmentMap;
+
+    const WIDTH: usize = 8;
+    const HEIGHT: usize = 4;
+
+    // Dim everywhere but one texel, rotated by rotation degrees
+    fn map(rotation: f64) -> EnvironmentMap {
+        let mut pixels: Vec<[f64; 3]> = vec![[0.1, 0.1, 0.1]; WIDTH*HEIGHT];
+        pixels[WIDTH + 5] = [100.0, 100.0, 100.0];
+        EnvironmentMap::new(WIDTH, HEIGHT, pixels, rotation, 1.0)
+    }
+
+    #[test]
+    fn pdf_integrates_to_one() {
+        for rotation in [0.0, 30.0] {
+            let integral: f64 = integrate(&BackgroundPdf::new(&map(rotation)));
+            assert!((integral - 1.0).abs() < 1e-3, "{}", integral);
+        }
+    }
+
+    #[test]
+    fn random_matches_pdf() {
+        for rotation in [0.0, 30.0] {
+            assert_generate_matches_value(&BackgroundPdf::new(&map(rotation)), &mut new_rng(0, 0), 0.005);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,39 @@
+use image::Rgb;
+
+use crate::{
+    ray::Ray,
+    rgb_wrapper::RgbWrapper,
+    vec3::Vec3};
+
+use super::background::Background;
+
+// Linear gradient between two colors based on the height of the ray direction
+#[derive(Clone, Copy)]
+pub struct Gradient {
+    pub bottom: RgbWrapper,
+    pub top: RgbWrapper,
+}
+
+impl Gradient {
+    pub fn new(bottom: Rgb<f64>, top: Rgb<f64>) -> Self {
+        Self {
+            bottom: RgbWrapper(bottom),
+            top: RgbWrapper(top)
+        }
+    }
+}
+
+impl Default for Gradient {
+    // White to sky blue
+    fn default() -> Self {
+        Self::new(Rgb::from([1.0, 1.0, 1.0]), Rgb::from([0.5, 0.7, 1.0]))
+    }
+}
+
+impl Background for Gradient {
+    fn color(&self, ray: &Ray) -> RgbWrapper {
+        let unit_direction: Vec3 = ray.direction().unit_vector();
+        let t: f64 = 0.5 * (unit_direction.y() + 1.0);
+        self.bottom*(1.0-t) + self.top*t
+    }
+}
\ No newline at end of file
@@ -0,0 +1,4 @@
+pub mod background;
+pub mod gradient;
+pub mod constant;
+pub mod environment_map;
\ No newline at end of file
@@ -1,4 +1,4 @@
-use crate::vec3::{Point3, Vec3, F64Multiplier};
+use crate::vec3::{Point3, Vec3, F64Multiplier, sample_unit_disk};
 use crate::ray::Ray;
 
 #[derive(Copy, Clone)]
@@ -7,29 +7,67 @@ pub struct Camera {
     lower_left_corner: Point3,
     horizontal: Vec3,
     vertical: Vec3,
+    u: Vec3,
+    v: Vec3,
+    lens_radius: f64,
+    // Shutter open and close times, rays are spread evenly over the interval
+    time0: f64,
+    time1: f64,
 }
 
 impl Camera {
-    pub fn new(aspect_ratio: f64, viewport_height: f64, focal_length: f64) -> Self {
+    // vfov is the vertical field of view in degrees. An aperture of zero gives a
+    // pinhole camera, anything larger blurs objects away from the focus distance.
+    // Moving objects are blurred over the time the shutter is open.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        look_from: Point3,
+        look_at: Point3,
+        vup: Vec3,
+        vfov: f64,
+        aspect_ratio: f64,
+        aperture: f64,
+        focus_dist: f64,
+        shutter_open: f64,
+        shutter_close: f64
+    ) -> Self {
+        let theta: f64 = vfov.to_radians();
+        let h: f64 = (theta/2.0).tan();
+        let viewport_height: f64 = 2.0 * h;
         let viewport_width: f64 = aspect_ratio * viewport_height;
 
-        let origin: Point3 = Point3::new(0.0, 0.0, 0.0);
-        let horizontal: Vec3 = Vec3::new(viewport_width, 0.0, 0.0);
-        let vertical: Vec3 = Vec3::new(0.0, viewport_height, 0.0);
-        let lower_left_corner: Point3 = origin - horizontal/2.0 - vertical/2.0 - Vec3::new(0.0, 0.0, focal_length);
+        let w: Vec3 = (look_from - look_at).unit_vector();
+        let u: Vec3 = vup.cross(w).unit_vector();
+        let v: Vec3 = w.cross(u);
+
+        let origin: Point3 = look_from;
+        let horizontal: Vec3 = F64Multiplier(focus_dist * viewport_width) * u;
+        let vertical: Vec3 = F64Multiplier(focus_dist * viewport_height) * v;
+        let lower_left_corner: Point3 = origin - horizontal/2.0 - vertical/2.0 - F64Multiplier(focus_dist) * w;
 
         Self {
             origin,
             lower_left_corner,
             horizontal,
-            vertical
+            vertical,
+            u,
+            v,
+            lens_radius: aperture / 2.0,
+            time0: shutter_open,
+            time1: shutter_close
         }
     }
 
-    pub fn get_ray(&self, u: f64, v: f64) -> Ray {
+    // lens is a point in the unit square that picks where on the lens the ray
+    // starts, time in [0, 1) picks when it leaves while the shutter is open
+    pub fn get_ray(&self, s: f64, t: f64, lens: [f64; 2], time: f64) -> Ray {
+        let rd: Vec3 = F64Multiplier(self.lens_radius) * sample_unit_disk(lens);
+        let offset: Vec3 = F64Multiplier(rd.x()) * self.u + F64Multiplier(rd.y()) * self.v;
+
         Ray::new(
-            self.origin,
-            self.lower_left_corner + F64Multiplier(u)*self.horizontal + F64Multiplier(v)*self.vertical - self.origin
+            self.origin + offset,
+            self.lower_left_corner + F64Multiplier(s)*self.horizontal + F64Multiplier(t)*self.vertical - self.origin - offset,
+            self.time0 + (self.time1 - self.time0) * time
         )
     }
 }
\ No newline at end of file
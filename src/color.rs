@@ -1,27 +1,31 @@
 use image::Rgb;
 
 use crate::{
+    backgrounds::background::Background,
     utils::clamp,
-    hittables::hittable::{Hittable, HitRecord},
+    hittables::{hittable::{Hittable, HitRecord, MediumSegment}, hittable_list::HittableList},
+    materials::material::{Material, Scatter, ScatterRecord},
+    pdfs::{pdf::Pdf, hittable_pdf::HittablePdf, background_pdf::BackgroundPdf, mixture_pdf::MixturePdf},
+    render_settings::RenderSettings,
+    samplers::sampler::Sampler,
     rgb_wrapper::RgbWrapper,
     ray::Ray,
-    vec3::{Point3, random_unit_vector, Vec3}};
+    vec3::Point3};
 
 const COLOR_MULTIPLIER: f64 = 256.0;
 const CLAMP_MIN: f64 = 0.0;
 const CLAMP_MAX: f64 = 0.999;
 
-pub fn write_color(pixel_color: Rgb<f64>, samples_per_pixel: u32, gamma: f64) -> Rgb<u8> {
+pub fn write_color(pixel_color: Rgb<f64>, settings: &RenderSettings) -> Rgb<u8> {
     let mut r: f64 = pixel_color.0[0];
     let mut g: f64 = pixel_color.0[1];
     let mut b: f64 = pixel_color.0[2];
 
-    // Scale and gamma correct each pixel
-    let scale: f64 = 1.0 / (samples_per_pixel as f64);
-    let gamma_correct: f64 = 1.0 / gamma;
-    r = (scale * r).powf(gamma_correct);
-    g = (scale * g).powf(gamma_correct);
-    b = (scale * b).powf(gamma_correct);
+    // Gamma correct each pixel, the color is already averaged over its samples
+    let gamma_correct: f64 = 1.0 / settings.gamma;
+    r = r.powf(gamma_correct);
+    g = g.powf(gamma_correct);
+    b = b.powf(gamma_correct);
 
     Rgb::from([
         (COLOR_MULTIPLIER*clamp(r, CLAMP_MIN, CLAMP_MAX)) as u8,
@@ -30,20 +34,353 @@ pub fn write_color(pixel_color: Rgb<f64>, samples_per_pixel: u32, gamma: f64) ->
     ])
 }
 
-pub fn ray_color(ray: &Ray, world: &dyn Hittable, depth: u32) -> RgbWrapper {
+// What rays are traced against
+pub struct TraceScene<'a> {
+    pub world: &'a dyn Hittable,
+    // Lights that are sampled directly, they are part of the world as well
+    pub lights: &'a HittableList,
+    // Smoke and fog, they aren't part of the world
+    pub media: &'a HittableList,
+    pub background: &'a dyn Background,
+}
+
+// Path traces the ray. Every diffuse hit also samples a point on one of the
+// lights, or a direction towards the bright parts of the background, and sends a
+// shadow ray there (next-event estimation). The two ways of reaching a light
+// are combined with multiple importance sampling.
+//
+// Every bounce takes one dimension from the sampler for each medium, for the
+// distance the ray travels in it, then every hit takes two, one for the light
+// sample and one for the direction the material scatters in.
+pub fn ray_color(ray: &Ray, scene: &TraceScene, depth: u32, sampler: &mut dyn Sampler) -> RgbWrapper {
+    trace(ray, scene, depth, None, sampler)
+}
+
+// bsdf_pdf is the density the previous hit sampled this ray with, None for
+// camera rays and rays off mirror-like surfaces that light sampling can't reach
+fn trace(ray: &Ray, scene: &TraceScene, depth: u32, bsdf_pdf: Option<f64>, sampler: &mut dyn Sampler) -> RgbWrapper {
     let mut rec: HitRecord = HitRecord::new_empty();
 
     if depth == 0 {
         return RgbWrapper(Rgb::from([0.0, 0.0, 0.0]));
     }
 
-    if world.hit(ray, 0.0001, f64::INFINITY, &mut rec) {
-        let target: Point3 = rec.p + rec.normal + random_unit_vector();
-        return ray_color(&Ray::new(rec.p, target - rec.p), world, depth-1) * 0.5;
+    // Smoke and fog in front of the surface can scatter the ray first
+    let hit_surface: bool = scene.world.hit(ray, 0.0001, f64::INFINITY, &mut rec);
+    let t_max: f64 = if hit_surface { rec.t } else { f64::INFINITY };
+    let hit_medium: bool = scatter_in_media(ray, scene.media, t_max, &mut rec, sampler);
+
+    if hit_surface || hit_medium {
+        let material = match &rec.material {
+            None => return RgbWrapper(Rgb::from([0.0, 0.0, 0.0])),
+            Some(material) => material,
+        };
+        // Taken whether they're used or not, so each bounce sees the same dimensions
+        let light_sample: [f64; 2] = sampler.next_2d();
+        let bsdf_sample: [f64; 2] = sampler.next_2d();
+
+        let mut emitted: RgbWrapper = material.emitted(ray, &rec);
+        if let Some(bsdf_pdf) = bsdf_pdf {
+            if !is_black(&emitted) {
+                let light_pdf: f64 = light_pdf(scene, ray.origin()).value(&ray.direction());
+                emitted = emitted * power_heuristic(bsdf_pdf, light_pdf);
+            }
+        }
+
+        let pdf: Box<dyn Pdf> = match material.scatter(ray, &rec, sampler.rng()) {
+            None => return emitted,
+            Some(ScatterRecord::Specular { attenuation, ray: scattered }) =>
+                return emitted + attenuation * trace(&scattered, scene, depth-1, None, sampler),
+            Some(ScatterRecord::Sampled { pdf }) => pdf,
+        };
+
+        let direct: RgbWrapper = sample_light(ray, &rec, material, pdf.as_ref(), scene, light_sample, sampler);
+
+        // Pdfs return the zero vector when they fail to produce a direction
+        let scattered: Ray = Ray::new(rec.p, pdf.generate(bsdf_sample, sampler.rng()), ray.time());
+        if scattered.direction().near_zero() {
+            return emitted + direct;
+        }
+        let pdf_value: f64 = pdf.value(&scattered.direction());
+        if pdf_value <= 0.0 {
+            return emitted + direct;
+        }
+        let weight: RgbWrapper = material.eval(ray, &rec, &scattered) * (1.0 / pdf_value);
+        return emitted + direct + weight * trace(&scattered, scene, depth-1, Some(pdf_value), sampler);
+    }
+
+    let color: RgbWrapper = scene.background.color(ray);
+    match bsdf_pdf {
+        Some(bsdf_pdf) if scene.background.importance_sampled() =>
+            color * power_heuristic(bsdf_pdf, light_pdf(scene, ray.origin()).value(&ray.direction())),
+        _ => color,
+    }
+}
+
+// Samples the lights and, if it supports it, the background
+fn light_pdf<'a>(scene: &TraceScene<'a>, origin: Point3) -> MixturePdf<'a> {
+    let mut components: Vec<(f64, Box<dyn Pdf + 'a>)> = Vec::new();
+    if !scene.lights.is_empty() {
+        components.push((1.0, Box::new(HittablePdf::new(scene.lights, origin))));
+    }
+    if scene.background.importance_sampled() {
+        components.push((1.0, Box::new(BackgroundPdf::new(scene.background))));
+    }
+    MixturePdf::new(components)
+}
+
+// Light arriving directly from the point on the lights or the background picked
+// by u, weighted against the chance of the material's pdf having picked the same direction
+fn sample_light(ray: &Ray, rec: &HitRecord, material: &Material, bsdf_pdf: &dyn Pdf, scene: &TraceScene, u: [f64; 2], sampler: &mut dyn Sampler) -> RgbWrapper {
+    let black: RgbWrapper = RgbWrapper(Rgb::from([0.0, 0.0, 0.0]));
+
+    let light_pdf: MixturePdf = light_pdf(scene, rec.p);
+    let shadow_ray: Ray = Ray::new(rec.p, light_pdf.generate(u, sampler.rng()), ray.time());
+    if shadow_ray.direction().near_zero() {
+        return black;
+    }
+    let light_pdf_value: f64 = light_pdf.value(&shadow_ray.direction());
+    if light_pdf_value <= 0.0 {
+        return black;
+    }
+    let f: RgbWrapper = material.eval(ray, rec, &shadow_ray);
+    if is_black(&f) {
+        return black;
+    }
+
+    // Whatever the shadow ray hits first is what lights the point, an occluder
+    // that doesn't glow blocks the light
+    let mut light_rec: HitRecord = HitRecord::new_empty();
+    let hit_light: bool = scene.world.hit(&shadow_ray, 0.0001, f64::INFINITY, &mut light_rec);
+    let emitted: RgbWrapper = if !hit_light {
+        if !scene.background.importance_sampled() {
+            return black;
+        }
+        scene.background.color(&shadow_ray)
+    } else {
+        match &light_rec.material {
+            None => return black,
+            Some(light_material) => light_material.emitted(&shadow_ray, &light_rec),
+        }
+    };
+    if is_black(&emitted) {
+        return black;
+    }
+
+    // Smoke and fog on the way let only some of the light through
+    let t_light: f64 = if hit_light { light_rec.t } else { f64::INFINITY };
+    let transmittance: f64 = transmittance(&shadow_ray, scene.media, t_light);
+
+    let weight: f64 = power_heuristic(light_pdf_value, bsdf_pdf.value(&shadow_ray.direction()));
+    f * emitted * (weight * transmittance / light_pdf_value)
+}
+
+// Each medium the ray crosses before t_max scatters it after an exponentially
+// distributed distance inside, drawn from one sampler dimension per medium. rec
+// becomes the closest of the scattering points, if there are any.
+fn scatter_in_media(ray: &Ray, media: &HittableList, t_max: f64, rec: &mut HitRecord, sampler: &mut dyn Sampler) -> bool {
+    let ray_length: f64 = ray.direction().length();
+    let mut closest_so_far: f64 = t_max;
+    let mut scattered: bool = false;
+
+    for medium in &media.objects {
+        // Taken whether the ray reaches the medium or not, so later bounces see the same dimensions
+        let u: f64 = sampler.next_1d();
+        let mut medium_rec: HitRecord = HitRecord::new_empty();
+        if !medium.hit(ray, 0.0001, closest_so_far, &mut medium_rec) {
+            continue;
+        }
+        let segment: MediumSegment = match medium_rec.medium {
+            None => continue,
+            Some(segment) => segment,
+        };
+
+        // Distances are measured along the world space ray, so density is per world unit
+        let distance: f64 = -(1.0 - u).ln() / segment.density;
+        let t: f64 = medium_rec.t + distance / ray_length;
+        if t < segment.t_exit {
+            medium_rec.t = t;
+            medium_rec.p = ray.at(t);
+            *rec = medium_rec;
+            closest_so_far = t;
+            scattered = true;
+        }
+    }
+
+    scattered
+}
+
+// Fraction of the light that gets through the media between the ray's origin and t_max
+fn transmittance(ray: &Ray, media: &HittableList, t_max: f64) -> f64 {
+    let ray_length: f64 = ray.direction().length();
+    let mut transmittance: f64 = 1.0;
+
+    for medium in &media.objects {
+        let mut medium_rec: HitRecord = HitRecord::new_empty();
+        if !medium.hit(ray, 0.0001, t_max, &mut medium_rec) {
+            continue;
+        }
+        if let Some(segment) = medium_rec.medium {
+            transmittance *= (-segment.density * (segment.t_exit - medium_rec.t) * ray_length).exp();
+        }
+    }
+
+    transmittance
+}
+
+// Multiple importance sampling weight for a sample taken with the strategy of
+// density pdf, when other_pdf could have produced it as well
+fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let a: f64 = pdf*pdf;
+    let b: f64 = other_pdf*other_pdf;
+    if a + b == 0.0 { 0.0 } else { a / (a + b) }
+}
+
+fn is_black(color: &RgbWrapper) -> bool {
+    color.0 == Rgb::from([0.0, 0.0, 0.0])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use image::Rgb;
+
+    use crate::{
+        backgrounds::constant::Constant,
+        hittables::{hittable::{Hittable, HitRecord}, hittable_list::HittableList, quad::Quad, sphere::Sphere, constant_medium::ConstantMedium, transformed::Transformed},
+        materials::{diffuse_light::DiffuseLight, isotropic::Isotropic, lambertian::Lambertian, material::Material},
+        mat4::Mat4,
+        ray::Ray,
+        samplers::{independent::IndependentSampler, sampler::Sampler},
+        utils::new_rng,
+        vec3::{Point3, Vec3}};
+
+    use super::{TraceScene, ray_color, scatter_in_media, transmittance, power_heuristic};
+
+    const RAYS: u32 = 20000;
+    // Paths traced for each way of lighting the plane
+    const SAMPLES: u32 = 100000;
+
+    // Medium filling a sphere of radius one at the origin, scaled by scale
+    fn media(density: f64, scale: f64) -> HittableList {
+        let phase_function: Material = Material::new(Arc::new(Isotropic::new(Rgb::from([0.5, 0.5, 0.5]))));
+        let boundary: Arc<dyn Hittable + Send + Sync> = Arc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, phase_function.clone()));
+        let medium: ConstantMedium = ConstantMedium::new(boundary, density, phase_function);
+        let transformed: Transformed = Transformed::new(Arc::new(medium), Mat4::scaling(Vec3::new(scale, scale, scale))).unwrap();
+        HittableList::new(Arc::new(transformed))
+    }
+
+    // Fraction of rays through the middle of the media that don't scatter
+    fn fraction_through(media: &HittableList, ray: &Ray) -> f64 {
+        let mut sampler: IndependentSampler = IndependentSampler::new(new_rng(1, 0));
+        let mut through: u32 = 0;
+        for index in 0..RAYS {
+            sampler.start_sample(index);
+            let mut rec: HitRecord = HitRecord::new_empty();
+            if !scatter_in_media(ray, media, f64::INFINITY, &mut rec, &mut sampler) {
+                through += 1;
+            }
+        }
+        through as f64 / RAYS as f64
+    }
+
+    fn assert_fraction(actual: f64, expected: f64) {
+        // Four standard deviations of the fraction
+        let tolerance: f64 = 4.0 * (expected * (1.0 - expected) / RAYS as f64).sqrt();
+        assert!((actual - expected).abs() < tolerance, "{} passed through, expected {}", actual, expected);
+    }
+
+    #[test]
+    fn fraction_through_a_medium_falls_off_exponentially() {
+        for density in [0.1, 0.5, 1.0] {
+            // Two units inside the sphere, whatever the length of the direction
+            for direction in [Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.25, 0.0, 0.0)] {
+                let ray: Ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), direction, 0.0);
+                assert_fraction(fraction_through(&media(density, 1.0), &ray), (-density * 2.0).exp());
+            }
+        }
+    }
+
+    #[test]
+    fn transmittance_is_exponential_in_distance() {
+        let media: HittableList = media(0.5, 1.0);
+        let ray: Ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0), 0.0);
+
+        assert!((transmittance(&ray, &media, f64::INFINITY) - (-0.5f64 * 2.0).exp()).abs() < 1e-9);
+        // A surface in the middle of the medium at t = 2.5 only sees half of it
+        assert!((transmittance(&ray, &media, 2.5) - (-0.5f64 * 1.0).exp()).abs() < 1e-9);
+        // Rays that miss it aren't attenuated
+        let miss: Ray = Ray::new(Point3::new(-5.0, 3.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+        assert_eq!(transmittance(&miss, &media, f64::INFINITY), 1.0);
+    }
+
+    #[test]
+    fn scaled_media_measure_density_in_world_units() {
+        // Scaled by two, the ray crosses four units of it
+        let media: HittableList = media(0.5, 2.0);
+        let ray: Ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0), 0.0);
+
+        assert!((transmittance(&ray, &media, f64::INFINITY) - (-0.5f64 * 4.0).exp()).abs() < 1e-9);
+        assert_fraction(fraction_through(&media, &ray), (-0.5f64 * 4.0).exp());
+    }
+
+    #[test]
+    fn power_heuristic_weights_sum_to_one() {
+        for (pdf, other_pdf) in [(1.0, 1.0), (0.3, 2.0), (5.0, 0.01), (0.0, 1.0)] {
+            let sum: f64 = power_heuristic(pdf, other_pdf) + power_heuristic(other_pdf, pdf);
+            assert!((sum - 1.0).abs() < 1e-12, "{}", sum);
+        }
+    }
+
+    #[test]
+    fn power_heuristic_gives_no_weight_to_zero_pdfs() {
+        assert_eq!(power_heuristic(0.0, 0.0), 0.0);
+        assert_eq!(power_heuristic(0.0, 1.0), 0.0);
+        assert_eq!(power_heuristic(1.0, 0.0), 1.0);
+    }
+
+    // Mean and standard error of the light reaching a point on a diffuse plane
+    // under a square light, with it sampled directly when it's in lights
+    fn lit_plane(lights: &HittableList) -> (f64, f64) {
+        let plane: Material = Material::new(Arc::new(Lambertian::new(Rgb::from([0.5, 0.5, 0.5]))));
+        let light: Material = Material::new(Arc::new(DiffuseLight::new(Rgb::from([4.0, 4.0, 4.0]))));
+        let mut world: HittableList = HittableList::new(Arc::new(Quad::new(Point3::new(-5.0, -5.0, 0.0), Vec3::new(10.0, 0.0, 0.0), Vec3::new(0.0, 10.0, 0.0), plane)));
+        // Facing down onto the plane
+        world.add(Arc::new(Quad::new(Point3::new(-1.0, -1.0, 1.0), Vec3::new(0.0, 2.0, 0.0), Vec3::new(2.0, 0.0, 0.0), light)));
+        let scene: TraceScene = TraceScene {
+            world: &world,
+            lights,
+            media: &HittableList::new_empty(),
+            background: &Constant::new(Rgb::from([0.0, 0.0, 0.0])),
+        };
+
+        let ray: Ray = Ray::new(Point3::new(0.3, 0.2, 0.5), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let mut sampler: IndependentSampler = IndependentSampler::new(new_rng(2, 0));
+        let mut sum: f64 = 0.0;
+        let mut sum_squares: f64 = 0.0;
+        for index in 0..SAMPLES {
+            sampler.start_sample(index);
+            let value: f64 = ray_color(&ray, &scene, 2, &mut sampler).0.0[0];
+            sum += value;
+            sum_squares += value*value;
+        }
+        let mean: f64 = sum / SAMPLES as f64;
+        let variance: f64 = sum_squares / SAMPLES as f64 - mean*mean;
+        (mean, (variance / SAMPLES as f64).sqrt())
+    }
+
+    #[test]
+    fn light_sampling_converges_to_the_same_value_as_material_sampling() {
+        let light: Material = Material::new(Arc::new(DiffuseLight::new(Rgb::from([4.0, 4.0, 4.0]))));
+        let lights: HittableList = HittableList::new(Arc::new(Quad::new(Point3::new(-1.0, -1.0, 1.0), Vec3::new(0.0, 2.0, 0.0), Vec3::new(2.0, 0.0, 0.0), light)));
+
+        let (with_lights, with_lights_error) = lit_plane(&lights);
+        let (without_lights, without_lights_error) = lit_plane(&HittableList::new_empty());
+        // Four standard deviations of the difference
+        let tolerance: f64 = 4.0 * (with_lights_error*with_lights_error + without_lights_error*without_lights_error).sqrt();
+        assert!((with_lights - without_lights).abs() < tolerance, "{} with the light sampled, {} without", with_lights, without_lights);
+        // With less noise, which is what sampling the light is for
+        assert!(with_lights_error < without_lights_error);
     }
-    let unit_direction: Vec3 = ray.direction().unit_vector();
-    let t: f64 = 0.5 * (unit_direction.y() + 1.0);
-    RgbWrapper(
-        (RgbWrapper(Rgb::from([1.0, 1.0, 1.0]))*(1.0-t) + RgbWrapper(Rgb::from([0.5, 0.7, 1.0]))*t).0
-    )
 }
\ No newline at end of file
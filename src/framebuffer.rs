@@ -0,0 +1,43 @@
+use image::{Rgb, RgbImage, ImageBuffer};
+
+use crate::{color::write_color, render_settings::RenderSettings};
+
+// Floating point image holding the linear radiance of each pixel, averaged over
+// its samples. Coordinates start from the top left like the image crate.
+#[derive(Clone)]
+pub struct FrameBuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<Rgb<f64>>,
+}
+
+impl FrameBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Rgb::from([0.0, 0.0, 0.0]); (width * height) as usize]
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_pixel(&self, x: u32, y: u32) -> Rgb<f64> {
+        self.pixels[(y * self.width + x) as usize]
+    }
+
+    pub fn put_pixel(&mut self, x: u32, y: u32, pixel: Rgb<f64>) {
+        self.pixels[(y * self.width + x) as usize] = pixel;
+    }
+
+    // Gamma corrects and quantizes every pixel for 8 bit image formats
+    pub fn to_rgb_image(&self, settings: &RenderSettings) -> RgbImage {
+        ImageBuffer::from_fn(self.width, self.height, |x, y| write_color(self.get_pixel(x, y), settings))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,79 @@
+use crate::vec3::Point3;
+use crate::ray::Ray;
+
+// Axis-aligned bounding box
+#[derive(Clone, Copy)]
+pub struct Aabb {
+    pub minimum: Point3,
+    pub maximum: Point3,
+}
+
+impl Aabb {
+    // An empty box that contains nothing and is never hit
+    pub fn new_empty() -> Self {
+        Self {
+            minimum: Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            maximum: Point3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY)
+        }
+    }
+
+    pub fn new(minimum: Point3, maximum: Point3) -> Self {
+        Self {
+            minimum,
+            maximum
+        }
+    }
+
+    pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        let mut t_min: f64 = t_min;
+        let mut t_max: f64 = t_max;
+
+        for a in 0..3 {
+            let inv_d: f64 = 1.0 / r.direction()[a];
+            let mut t0: f64 = (self.minimum[a] - r.origin()[a]) * inv_d;
+            let mut t1: f64 = (self.maximum[a] - r.origin()[a]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max <= t_min {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    pub fn surrounding_box(box0: &Aabb, box1: &Aabb) -> Aabb {
+        let small: Point3 = Point3::new(
+            box0.minimum.x().min(box1.minimum.x()),
+            box0.minimum.y().min(box1.minimum.y()),
+            box0.minimum.z().min(box1.minimum.z())
+        );
+        let big: Point3 = Point3::new(
+            box0.maximum.x().max(box1.maximum.x()),
+            box0.maximum.y().max(box1.maximum.y()),
+            box0.maximum.z().max(box1.maximum.z())
+        );
+
+        Aabb::new(small, big)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.minimum.x() > self.maximum.x() || self.minimum.y() > self.maximum.y() || self.minimum.z() > self.maximum.z()
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        (self.minimum + self.maximum) / 2.0
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        let extent: Point3 = self.maximum - self.minimum;
+        2.0 * (extent.x()*extent.y() + extent.y()*extent.z() + extent.z()*extent.x())
+    }
+}
\ No newline at end of file
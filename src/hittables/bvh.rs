@@ -0,0 +1,227 @@
+use std::{sync::Arc, cmp::Ordering};
+
+use crate::ray::Ray;
+
+use super::{hittable::{Hittable, HitRecord}, hittable_list::HittableList, aabb::Aabb};
+
+// Bounding volume hierarchy over a set of hittables. Each node stores the box
+// surrounding both children so whole subtrees can be skipped with one box test.
+#[derive(Clone)]
+pub struct BvhNode {
+    left: Arc<dyn Hittable + Send + Sync>,
+    right: Arc<dyn Hittable + Send + Sync>,
+    bbox: Aabb,
+}
+
+struct BvhItem {
+    object: Arc<dyn Hittable + Send + Sync>,
+    bbox: Aabb,
+}
+
+impl BvhNode {
+    pub fn new(list: &HittableList) -> Self {
+        let items: Vec<BvhItem> = list.objects.iter()
+            .map(|object| BvhItem { object: Arc::clone(object), bbox: object.bounding_box() })
+            .collect();
+
+        Self::build(items)
+    }
+
+    fn build(mut items: Vec<BvhItem>) -> Self {
+        match items.len() {
+            0 => Self {
+                left: Arc::new(HittableList::new_empty()),
+                right: Arc::new(HittableList::new_empty()),
+                bbox: Aabb::new_empty()
+            },
+            1 => {
+                let item: BvhItem = items.remove(0);
+                Self {
+                    left: item.object,
+                    right: Arc::new(HittableList::new_empty()),
+                    bbox: item.bbox
+                }
+            }
+            2 => {
+                let second: BvhItem = items.remove(1);
+                let first: BvhItem = items.remove(0);
+                Self {
+                    bbox: Aabb::surrounding_box(&first.bbox, &second.bbox),
+                    left: first.object,
+                    right: second.object
+                }
+            }
+            _ => {
+                let (axis, split) = Self::surface_area_heuristic_split(&mut items);
+                sort_along_axis(&mut items, axis);
+
+                let right_items: Vec<BvhItem> = items.split_off(split);
+                let left: BvhNode = Self::build(items);
+                let right: BvhNode = Self::build(right_items);
+
+                Self {
+                    bbox: Aabb::surrounding_box(&left.bbox, &right.bbox),
+                    left: Arc::new(left),
+                    right: Arc::new(right)
+                }
+            }
+        }
+    }
+
+    // Finds the axis and index to split the items at which minimizes the
+    // surface area heuristic cost: SA(left) * N(left) + SA(right) * N(right)
+    fn surface_area_heuristic_split(items: &mut [BvhItem]) -> (usize, usize) {
+        let n: usize = items.len();
+        let mut best: (usize, usize) = (0, n / 2);
+        let mut best_cost: f64 = f64::INFINITY;
+
+        for axis in 0..3 {
+            sort_along_axis(items, axis);
+
+            // right_areas[i] is the surface area of the box around items[i..]
+            let mut right_areas: Vec<f64> = vec![0.0; n];
+            let mut right_box: Aabb = Aabb::new_empty();
+            for i in (0..n).rev() {
+                right_box = Aabb::surrounding_box(&right_box, &items[i].bbox);
+                right_areas[i] = right_box.surface_area();
+            }
+
+            let mut left_box: Aabb = Aabb::new_empty();
+            for split in 1..n {
+                left_box = Aabb::surrounding_box(&left_box, &items[split - 1].bbox);
+                let cost: f64 = left_box.surface_area() * split as f64 + right_areas[split] * (n - split) as f64;
+                if cost < best_cost {
+                    best_cost = cost;
+                    best = (axis, split);
+                }
+            }
+        }
+
+        best
+    }
+}
+
+fn sort_along_axis(items: &mut [BvhItem], axis: usize) {
+    items.sort_by(|a, b| {
+        a.bbox.centroid()[axis]
+            .partial_cmp(&b.bbox.centroid()[axis])
+            .unwrap_or(Ordering::Equal)
+    });
+}
+
+impl Hittable for BvhNode {
+    fn hit (&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        if !self.bbox.hit(r, t_min, t_max) {
+            return false;
+        }
+
+        let hit_left: bool = self.left.hit(r, t_min, t_max, rec);
+        let hit_right: bool = self.right.hit(r, t_min, if hit_left { rec.t } else { t_max }, rec);
+
+        hit_left || hit_right
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        hittables::{hittable::{Hittable, HitRecord}, hittable_list::HittableList, sphere::Sphere},
+        ray::Ray,
+        vec3::{Point3, Vec3}};
+
+    use super::BvhNode;
+
+    fn random_world(rng: &mut StdRng, count: usize) -> HittableList {
+        let mut world: HittableList = HittableList::new_empty();
+        for _ in 0..count {
+            let center: Point3 = Point3::new(rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0));
+            let mut sphere: Sphere = Sphere::new_empty();
+            sphere.center = center;
+            sphere.radius = rng.gen_range(0.1..1.5);
+            world.add(Arc::new(sphere));
+        }
+        world
+    }
+
+    fn random_ray(rng: &mut StdRng) -> Ray {
+        let origin: Point3 = Point3::new(rng.gen_range(-15.0..15.0), rng.gen_range(-15.0..15.0), rng.gen_range(-15.0..15.0));
+        let direction: Vec3 = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+        Ray::new(origin, direction, 0.0)
+    }
+
+    fn assert_same_hits(world: &HittableList, bvh: &BvhNode, rng: &mut StdRng, rays: usize) {
+        for _ in 0..rays {
+            let r: Ray = random_ray(rng);
+            let mut list_rec: HitRecord = HitRecord::new_empty();
+            let mut bvh_rec: HitRecord = HitRecord::new_empty();
+
+            let list_hit: bool = world.hit(&r, 0.0001, f64::INFINITY, &mut list_rec);
+            let bvh_hit: bool = bvh.hit(&r, 0.0001, f64::INFINITY, &mut bvh_rec);
+
+            assert_eq!(list_hit, bvh_hit);
+            if list_hit {
+                assert_eq!(list_rec.t, bvh_rec.t);
+                assert_eq!(list_rec.front_face, bvh_rec.front_face);
+                for axis in 0..3 {
+                    assert_eq!(list_rec.p[axis], bvh_rec.p[axis]);
+                    assert_eq!(list_rec.normal[axis], bvh_rec.normal[axis]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn matches_linear_list() {
+        let mut rng: StdRng = StdRng::seed_from_u64(7);
+        let world: HittableList = random_world(&mut rng, 500);
+        let bvh: BvhNode = BvhNode::new(&world);
+
+        assert_same_hits(&world, &bvh, &mut rng, 5000);
+    }
+
+    #[test]
+    fn matches_linear_list_for_small_worlds() {
+        let mut rng: StdRng = StdRng::seed_from_u64(11);
+        for count in 1..6 {
+            let world: HittableList = random_world(&mut rng, count);
+            let bvh: BvhNode = BvhNode::new(&world);
+
+            assert_same_hits(&world, &bvh, &mut rng, 500);
+        }
+    }
+
+    #[test]
+    fn empty_world_is_never_hit() {
+        let mut rng: StdRng = StdRng::seed_from_u64(3);
+        let bvh: BvhNode = BvhNode::new(&HittableList::new_empty());
+        let mut rec: HitRecord = HitRecord::new_empty();
+
+        for _ in 0..100 {
+            assert!(!bvh.hit(&random_ray(&mut rng), 0.0001, f64::INFINITY, &mut rec));
+        }
+    }
+
+    #[test]
+    fn bounding_box_contains_all_objects() {
+        let mut rng: StdRng = StdRng::seed_from_u64(5);
+        let world: HittableList = random_world(&mut rng, 100);
+        let bvh: BvhNode = BvhNode::new(&world);
+        let bbox = bvh.bounding_box();
+
+        for object in &world.objects {
+            let object_box = object.bounding_box();
+            for axis in 0..3 {
+                assert!(bbox.minimum[axis] <= object_box.minimum[axis]);
+                assert!(bbox.maximum[axis] >= object_box.maximum[axis]);
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use super::{hittable::{HitRecord, Hittable, MediumSegment}, aabb::Aabb};
+use crate::vec3::Vec3;
+use crate::ray::Ray;
+use crate::materials::material::Material;
+
+// Volume of constant density filling a closed boundary, like smoke or fog. Rays
+// travelling through it scatter after an exponentially distributed distance,
+// in a direction chosen by the phase function material. Hitting it finds the
+// stretch of the ray inside the boundary, the render picks the distance.
+#[derive(Clone)]
+pub struct ConstantMedium {
+    boundary: Arc<dyn Hittable + Send + Sync>,
+    density: f64,
+    phase_function: Material,
+}
+
+impl ConstantMedium {
+    pub fn new(boundary: Arc<dyn Hittable + Send + Sync>, density: f64, phase_function: Material) -> Self {
+        Self {
+            boundary,
+            density,
+            phase_function
+        }
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit (&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        // Find where the ray enters and leaves the boundary, even if it starts inside
+        let mut rec1: HitRecord = HitRecord::new_empty();
+        let mut rec2: HitRecord = HitRecord::new_empty();
+
+        if !self.boundary.hit(r, f64::NEG_INFINITY, f64::INFINITY, &mut rec1) {
+            return false;
+        }
+        if !self.boundary.hit(r, rec1.t + 0.0001, f64::INFINITY, &mut rec2) {
+            return false;
+        }
+
+        let t_enter: f64 = rec1.t.max(t_min);
+        let t_exit: f64 = rec2.t.min(t_max);
+        if t_enter >= t_exit {
+            return false;
+        }
+
+        rec.t = t_enter;
+        rec.p = r.at(rec.t);
+        // Scattering inside a volume has no surface, so these are arbitrary
+        rec.normal = Vec3::new(1.0, 0.0, 0.0);
+        rec.front_face = true;
+        rec.u = 0.0;
+        rec.v = 0.0;
+        rec.material = Some(self.phase_function.clone());
+        rec.medium = Some(MediumSegment { t_exit, density: self.density });
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+}
\ No newline at end of file
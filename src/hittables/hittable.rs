@@ -1,12 +1,27 @@
 use crate::vec3::{Point3, Vec3};
 use crate::ray::Ray;
+use crate::materials::material::Material;
 
-#[derive(Clone, Copy)]
+use super::aabb::Aabb;
+
+#[derive(Clone)]
 pub struct HitRecord {
     pub p: Point3,
     pub normal: Vec3,
+    pub material: Option<Material>,
     pub t: f64,
+    pub u: f64,
+    pub v: f64,
     pub front_face: bool,
+    // Set by media, the ray is inside one from t on, see ConstantMedium
+    pub medium: Option<MediumSegment>,
+}
+
+// Stretch of a ray inside a medium, up to t_exit
+#[derive(Clone, Copy, Debug)]
+pub struct MediumSegment {
+    pub t_exit: f64,
+    pub density: f64,
 }
 
 impl HitRecord {
@@ -14,8 +29,12 @@ impl HitRecord {
         HitRecord { 
             p: Point3::new_empty(),
             normal: Vec3::new_empty(),
+            material: None,
             t: 0.0,
-            front_face: false
+            u: 0.0,
+            v: 0.0,
+            front_face: false,
+            medium: None
          }
     }
 
@@ -27,4 +46,17 @@ impl HitRecord {
 
 pub trait Hittable {
     fn hit (&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool;
+    fn bounding_box(&self) -> Aabb;
+
+    // Probability density, per unit solid angle, of random() picking the given
+    // direction from origin. Zero for shapes that can't be sampled as lights.
+    fn pdf_value(&self, _origin: &Point3, _direction: &Vec3) -> f64 {
+        0.0
+    }
+
+    // Direction from origin towards the point on the shape picked by u, a point
+    // in the unit square
+    fn random(&self, _origin: &Point3, _u: [f64; 2]) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
 }
\ No newline at end of file
@@ -1,8 +1,10 @@
 use std::sync::Arc;
 
 use crate::ray::Ray;
+use crate::vec3::{Point3, Vec3};
+use crate::samplers::hash::ONE_MINUS_EPSILON;
 
-use super::hittable::{Hittable, HitRecord};
+use super::{hittable::{Hittable, HitRecord}, aabb::Aabb};
 
 #[derive(Clone)]
 pub struct HittableList {
@@ -31,6 +33,10 @@ impl HittableList {
     pub fn add(&mut self, object: Arc<dyn Hittable + Send + Sync>) {
         self.objects.push(object);
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
 }
 
 impl Hittable for HittableList {
@@ -43,10 +49,89 @@ impl Hittable for HittableList {
             if object.hit(r, t_min, closest_so_far, &mut temp_rec) {
                 hit_anything = true;
                 closest_so_far = temp_rec.t;
-                *rec = temp_rec;
+                *rec = temp_rec.clone();
             }
         }
 
         hit_anything
     }
+
+    fn bounding_box(&self) -> Aabb {
+        let mut output_box: Aabb = Aabb::new_empty();
+
+        for object in &self.objects {
+            output_box = Aabb::surrounding_box(&output_box, &object.bounding_box());
+        }
+
+        output_box
+    }
+
+    // Each object is equally likely to be sampled
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        if self.objects.is_empty() {
+            return 0.0;
+        }
+
+        let weight: f64 = 1.0 / self.objects.len() as f64;
+        self.objects.iter().map(|object| weight * object.pdf_value(origin, direction)).sum()
+    }
+
+    // The first dimension of u picks the object and the rest of it is stretched
+    // back over [0, 1) to pick the point on the object
+    fn random(&self, origin: &Point3, u: [f64; 2]) -> Vec3 {
+        if self.objects.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+
+        let scaled: f64 = u[0] * self.objects.len() as f64;
+        let index: usize = (scaled as usize).min(self.objects.len() - 1);
+        self.objects[index].random(origin, [(scaled - index as f64).min(ONE_MINUS_EPSILON), u[1]])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittables::{aabb::Aabb, hittable::{Hittable, HitRecord}},
+        ray::Ray,
+        samplers::hash::ONE_MINUS_EPSILON,
+        vec3::{Point3, Vec3}};
+
+    use super::HittableList;
+
+    // Never hit, random returns the u it was given with the number it was made with as z
+    struct EchoHittable(f64);
+
+    impl Hittable for EchoHittable {
+        fn hit(&self, _r: &Ray, _t_min: f64, _t_max: f64, _rec: &mut HitRecord) -> bool {
+            false
+        }
+
+        fn bounding_box(&self) -> Aabb {
+            Aabb::new_empty()
+        }
+
+        fn random(&self, _origin: &Point3, u: [f64; 2]) -> Vec3 {
+            Vec3::new(u[0], u[1], self.0)
+        }
+    }
+
+    #[test]
+    fn remapped_dimension_stays_in_the_unit_interval() {
+        let mut list: HittableList = HittableList::new_empty();
+        for index in 0..3 {
+            list.add(Arc::new(EchoHittable(index as f64)));
+        }
+        let origin: Point3 = Point3::new(0.0, 0.0, 0.0);
+
+        for i in 0..=999 {
+            let u: f64 = (i as f64 / 999.0).min(ONE_MINUS_EPSILON);
+            let direction: Vec3 = list.random(&origin, [u, 0.25]);
+            assert!((0.0..1.0).contains(&direction.x()), "u {}: {}", u, direction.x());
+            assert_eq!(direction.y(), 0.25);
+            assert_eq!(direction.z(), (u * 3.0).floor());
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,202 @@
+use std::{collections::HashMap, fmt::{self, Display}, error::Error, path::{Path, PathBuf}, sync::Arc};
+
+use image::{ImageError, Rgb};
+
+use super::{hittable::{HitRecord, Hittable}, hittable_list::HittableList, bvh::BvhNode, aabb::Aabb, triangle::Triangle};
+use crate::vec3::{Vec3, Point3};
+use crate::ray::Ray;
+use crate::materials::{material::Material, lambertian::Lambertian, metal::Metal, dielectric::Dielectric};
+use crate::textures::image_texture::ImageTexture;
+
+#[derive(Debug)]
+pub enum MeshError {
+    // The OBJ file could not be read or parsed
+    Obj { file: String, source: tobj::LoadError },
+    // The OBJ file names an MTL file that could not be read or parsed
+    Materials { file: String, source: tobj::LoadError },
+    // The diffuse texture of one of the MTL materials could not be loaded
+    Texture { file: String, material: String, source: ImageError },
+}
+
+impl Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::Obj { file, source } => write!(f, "Error loading mesh {}: {}", file, source),
+            MeshError::Materials { file, source } => write!(f, "Error loading the materials of mesh {}: {}", file, source),
+            MeshError::Texture { file, material, source } =>
+                write!(f, "Error loading texture {} for material {}: {}", file, material, source),
+        }
+    }
+}
+
+impl Error for MeshError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MeshError::Obj { source, .. } => Some(source),
+            MeshError::Materials { source, .. } => Some(source),
+            MeshError::Texture { source, .. } => Some(source),
+        }
+    }
+}
+
+// Triangle mesh loaded from a Wavefront OBJ file, stored in its own BVH
+#[derive(Clone)]
+pub struct Mesh {
+    triangles: BvhNode,
+    triangle_count: usize,
+}
+
+impl Mesh {
+    // Faces use the material named by their `usemtl` statement. MTL materials listed in
+    // material_overrides are replaced by the given material, the rest are converted to
+    // our closest material type. Faces without a material use default_material.
+    pub fn load(file: &Path, default_material: Material, material_overrides: &HashMap<String, Material>) -> Result<Self, MeshError> {
+        let load_options: tobj::LoadOptions = tobj::LoadOptions {
+            triangulate: true,
+            single_index: true,
+            ..Default::default()
+        };
+
+        let (models, mtl_materials) = match tobj::load_obj(file, &load_options) {
+            Err(ex) => return Err(MeshError::Obj { file: file.display().to_string(), source: ex }),
+            Ok(loaded) => loaded,
+        };
+
+        let mtl_materials: Vec<tobj::Material> = match mtl_materials {
+            Err(ex) => return Err(MeshError::Materials { file: file.display().to_string(), source: ex }),
+            Ok(mtl_materials) => mtl_materials,
+        };
+        let materials: Vec<Material> = mtl_materials.iter()
+            .map(|mtl| match material_overrides.get(&mtl.name) {
+                Some(material) => Ok(material.clone()),
+                None => material_from_mtl(mtl, file.parent().unwrap_or(Path::new(""))),
+            })
+            .collect::<Result<Vec<Material>, MeshError>>()?;
+
+        let mut triangles: HittableList = HittableList::new_empty();
+        for model in &models {
+            let mesh: &tobj::Mesh = &model.mesh;
+            let material: Material = match mesh.material_id.and_then(|id| materials.get(id)) {
+                Some(material) => material.clone(),
+                None => default_material.clone(),
+            };
+
+            let position = |i: usize| Point3::new(mesh.positions[3*i] as f64, mesh.positions[3*i + 1] as f64, mesh.positions[3*i + 2] as f64);
+            let normal = |i: usize| Vec3::new(mesh.normals[3*i] as f64, mesh.normals[3*i + 1] as f64, mesh.normals[3*i + 2] as f64);
+            let texcoord = |i: usize| (mesh.texcoords[2*i] as f64, mesh.texcoords[2*i + 1] as f64);
+
+            for face in mesh.indices.chunks_exact(3) {
+                let [i0, i1, i2] = [face[0] as usize, face[1] as usize, face[2] as usize];
+                let mut triangle: Triangle = Triangle::new(position(i0), position(i1), position(i2), material.clone());
+                if !mesh.normals.is_empty() {
+                    triangle = triangle.with_normals([normal(i0), normal(i1), normal(i2)]);
+                }
+                if !mesh.texcoords.is_empty() {
+                    triangle = triangle.with_uvs([texcoord(i0), texcoord(i1), texcoord(i2)]);
+                }
+                triangles.add(Arc::new(triangle));
+            }
+        }
+
+        Ok(Self {
+            triangle_count: triangles.objects.len(),
+            triangles: BvhNode::new(&triangles)
+        })
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangle_count
+    }
+}
+
+impl Hittable for Mesh {
+    fn hit (&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.triangles.hit(r, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.triangles.bounding_box()
+    }
+}
+
+// Maps an MTL material onto the closest of our materials using its illumination model.
+// Texture paths are relative to the directory of the OBJ file.
+fn material_from_mtl(mtl: &tobj::Material, directory: &Path) -> Result<Material, MeshError> {
+    let to_rgb = |color: [f32; 3]| Rgb::from([color[0] as f64, color[1] as f64, color[2] as f64]);
+    let diffuse: Rgb<f64> = to_rgb(mtl.diffuse.unwrap_or([0.8, 0.8, 0.8]));
+
+    let transparent: bool = mtl.dissolve.is_some_and(|d| d < 1.0);
+    Ok(match mtl.illumination_model {
+        Some(4) | Some(6) | Some(7) | Some(9) => transparent_material(mtl),
+        _ if transparent => transparent_material(mtl),
+        Some(3) | Some(5) | Some(8) => {
+            let specular: Rgb<f64> = mtl.specular.map_or(diffuse, to_rgb);
+            // Convert the Phong exponent into a roughness to use as the fuzz
+            let shininess: f64 = mtl.shininess.unwrap_or(0.0).max(0.0) as f64;
+            let fuzz: f64 = (2.0 / (shininess + 2.0)).sqrt();
+            Material::new(Arc::new(Metal::new(specular, fuzz)))
+        }
+        _ => match &mtl.diffuse_texture {
+            None => Material::new(Arc::new(Lambertian::new(diffuse))),
+            Some(texture_file) => {
+                let texture_path: PathBuf = directory.join(texture_file);
+                match ImageTexture::load(&texture_path) {
+                    Err(ex) => return Err(MeshError::Texture { file: texture_path.display().to_string(), material: mtl.name.clone(), source: ex }),
+                    Ok(texture) => Material::new(Arc::new(Lambertian::new_textured(Arc::new(texture)))),
+                }
+            }
+        },
+    })
+}
+
+fn transparent_material(mtl: &tobj::Material) -> Material {
+    Material::new(Arc::new(Dielectric::new(mtl.optical_density.unwrap_or(1.5) as f64)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, env, fs, path::PathBuf, sync::Arc};
+
+    use image::Rgb;
+
+    use crate::materials::{lambertian::Lambertian, material::Material};
+
+    use super::{Mesh, MeshError};
+
+    // Writes a one triangle OBJ file and its MTL file, if there is one, to a
+    // directory of its own and loads it
+    fn load(name: &str, mtl: Option<&str>, material_overrides: &HashMap<String, Material>) -> Result<Mesh, MeshError> {
+        let directory: PathBuf = env::temp_dir().join(format!("mesh_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("triangle.obj"), "mtllib triangle.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl paint\nf 1 2 3\n").unwrap();
+        if let Some(mtl) = mtl {
+            fs::write(directory.join("triangle.mtl"), mtl).unwrap();
+        }
+
+        let default_material: Material = Material::new(Arc::new(Lambertian::new(Rgb::from([0.8, 0.8, 0.8]))));
+        let mesh: Result<Mesh, MeshError> = Mesh::load(&directory.join("triangle.obj"), default_material, material_overrides);
+        fs::remove_dir_all(&directory).unwrap();
+        mesh
+    }
+
+    #[test]
+    fn missing_mtl_file_is_an_error() {
+        assert!(matches!(load("missing_mtl", None, &HashMap::new()), Err(MeshError::Materials { .. })));
+    }
+
+    #[test]
+    fn missing_texture_is_an_error() {
+        let mtl: &str = "newmtl paint\nKd 0.5 0.5 0.5\nmap_Kd missing.png\n";
+        match load("missing_texture", Some(mtl), &HashMap::new()) {
+            Err(MeshError::Texture { file, material, .. }) => {
+                assert!(file.ends_with("missing.png"), "{}", file);
+                assert_eq!(material, "paint");
+            }
+            other => panic!("expected the missing texture to be reported, got {:?}", other.map(|mesh| mesh.triangle_count())),
+        }
+
+        // Unless the material is replaced and the texture isn't needed
+        let overrides: HashMap<String, Material> = HashMap::from([("paint".to_string(), Material::new(Arc::new(Lambertian::new(Rgb::from([0.5, 0.5, 0.5])))))]);
+        assert_eq!(load("overridden_texture", Some(mtl), &overrides).unwrap().triangle_count(), 1);
+    }
+}
\ No newline at end of file
@@ -1,3 +1,11 @@
 pub mod sphere;
 pub mod hittable;
-pub mod hittable_list;
\ No newline at end of file
+pub mod hittable_list;
+pub mod aabb;
+pub mod bvh;
+pub mod triangle;
+pub mod mesh;
+pub mod quad;
+pub mod transformed;
+pub mod moving_sphere;
+pub mod constant_medium;
\ No newline at end of file
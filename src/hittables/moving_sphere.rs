@@ -0,0 +1,77 @@
+use super::{hittable::{HitRecord, Hittable}, aabb::Aabb, sphere::get_sphere_uv};
+use crate::vec3::{Vec3, Point3, F64Multiplier};
+use crate::ray::Ray;
+use crate::utils::clamp;
+use crate::materials::material::Material;
+
+// Sphere moving in a straight line, from center0 at time0 to center1 at time1.
+// It rests at center0 before time0 and at center1 after time1.
+#[derive(Clone)]
+pub struct MovingSphere {
+    pub center0: Point3,
+    pub center1: Point3,
+    pub time0: f64,
+    pub time1: f64,
+    pub radius: f64,
+    pub material: Material
+}
+
+impl MovingSphere {
+    pub fn new(center0: Point3, center1: Point3, time0: f64, time1: f64, radius: f64, material: Material) -> Self {
+        Self {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material
+        }
+    }
+
+    pub fn center(&self, time: f64) -> Point3 {
+        let fraction: f64 = clamp((time - self.time0) / (self.time1 - self.time0), 0.0, 1.0);
+        self.center0 + F64Multiplier(fraction) * (self.center1 - self.center0)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit (&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let center: Point3 = self.center(r.time());
+        let oc: Vec3 = r.origin() - center;
+        let a: f64 = r.direction().length_squared();
+        let half_b: f64 = oc.dot(r.direction());
+        let c: f64 = oc.length_squared() - self.radius*self.radius;
+
+        let discriminant: f64 = half_b*half_b - a*c;
+        if discriminant < 0.0 {
+            return false;
+        }
+
+        let sqrtd: f64 = discriminant.sqrt();
+
+        let mut root: f64 = (-half_b - sqrtd) / a;
+        if root < t_min || root > t_max {
+            root = (-half_b + sqrtd) / a;
+            if root < t_min || root > t_max {
+                return false;
+            }
+        }
+
+        rec.t = root;
+        rec.p = r.at(rec.t);
+        let outward_normal: Vec3 = (rec.p - center) / self.radius;
+        rec.set_face_normal(r, outward_normal);
+        (rec.u, rec.v) = get_sphere_uv(&outward_normal);
+        rec.material = Some(self.material.clone());
+
+        true
+    }
+
+    // Covers the whole path between the two centers
+    fn bounding_box(&self) -> Aabb {
+        let r: f64 = self.radius.abs();
+        let box0: Aabb = Aabb::new(self.center0 - Vec3::new(r, r, r), self.center0 + Vec3::new(r, r, r));
+        let box1: Aabb = Aabb::new(self.center1 - Vec3::new(r, r, r), self.center1 + Vec3::new(r, r, r));
+        Aabb::surrounding_box(&box0, &box1)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,121 @@
+use super::{hittable::{HitRecord, Hittable}, aabb::Aabb};
+use crate::vec3::{Vec3, Point3, F64Multiplier};
+use crate::ray::Ray;
+use crate::materials::material::Material;
+
+// Padding for the bounding box so quads lying in an axis plane still have volume
+const BOX_PADDING: f64 = 0.0001;
+const PARALLEL_EPSILON: f64 = 1e-8;
+
+// Parallelogram with one corner at q and sides u and v, a rectangle when they are perpendicular
+#[derive(Clone)]
+pub struct Quad {
+    pub q: Point3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: Material,
+    normal: Vec3,
+    d: f64,
+    w: Vec3,
+    area: f64,
+}
+
+impl Quad {
+    pub fn new(q: Point3, u: Vec3, v: Vec3, material: Material) -> Self {
+        let n: Vec3 = u.cross(v);
+        let normal: Vec3 = n.unit_vector();
+
+        Self {
+            q,
+            u,
+            v,
+            material,
+            normal,
+            d: normal.dot(q),
+            w: n / n.dot(n),
+            area: n.length()
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit (&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let denom: f64 = self.normal.dot(r.direction());
+
+        // No hit if the ray is parallel to the plane
+        if denom.abs() < PARALLEL_EPSILON {
+            return false;
+        }
+
+        let t: f64 = (self.d - self.normal.dot(r.origin())) / denom;
+        if t < t_min || t > t_max {
+            return false;
+        }
+
+        // Find the plane coordinates of the hit point and check they are inside the quad
+        let intersection: Point3 = r.at(t);
+        let planar_hit: Vec3 = intersection - self.q;
+        let alpha: f64 = self.w.dot(planar_hit.cross(self.v));
+        let beta: f64 = self.w.dot(self.u.cross(planar_hit));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return false;
+        }
+
+        rec.t = t;
+        rec.p = intersection;
+        rec.u = alpha;
+        rec.v = beta;
+        rec.set_face_normal(r, self.normal);
+        rec.material = Some(self.material.clone());
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let corners: [Point3; 4] = [self.q, self.q + self.u, self.q + self.v, self.q + self.u + self.v];
+        let padding: Vec3 = Vec3::new(BOX_PADDING, BOX_PADDING, BOX_PADDING);
+
+        let mut bbox: Aabb = Aabb::new_empty();
+        for corner in corners {
+            bbox = Aabb::surrounding_box(&bbox, &Aabb::new(corner - padding, corner + padding));
+        }
+
+        bbox
+    }
+
+    // Samples points uniformly over the area, converted to a density over directions
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        let mut rec: HitRecord = HitRecord::new_empty();
+        if !self.hit(&Ray::new(*origin, *direction, 0.0), 0.0001, f64::INFINITY, &mut rec) {
+            return 0.0;
+        }
+
+        let distance_squared: f64 = rec.t*rec.t*direction.length_squared();
+        let cosine: f64 = (direction.dot(rec.normal) / direction.length()).abs();
+        distance_squared / (cosine*self.area)
+    }
+
+    fn random(&self, origin: &Point3, u: [f64; 2]) -> Vec3 {
+        let p: Point3 = self.q + F64Multiplier(u[0])*self.u + F64Multiplier(u[1])*self.v;
+        p - *origin
+    }
+}
+
+// The six sides of the box spanning the two opposite corners a and b
+pub fn make_box(a: Point3, b: Point3, material: Material) -> Vec<Quad> {
+    let min: Point3 = Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z()));
+    let max: Point3 = Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z()));
+
+    let dx: Vec3 = Vec3::new(max.x() - min.x(), 0.0, 0.0);
+    let dy: Vec3 = Vec3::new(0.0, max.y() - min.y(), 0.0);
+    let dz: Vec3 = Vec3::new(0.0, 0.0, max.z() - min.z());
+
+    vec![
+        Quad::new(Point3::new(min.x(), min.y(), max.z()), dx, dy, material.clone()),      // front
+        Quad::new(Point3::new(max.x(), min.y(), max.z()), -dz, dy, material.clone()),     // right
+        Quad::new(Point3::new(max.x(), min.y(), min.z()), -dx, dy, material.clone()),     // back
+        Quad::new(Point3::new(min.x(), min.y(), min.z()), dz, dy, material.clone()),      // left
+        Quad::new(Point3::new(min.x(), max.y(), max.z()), dx, -dz, material.clone()),     // top
+        Quad::new(Point3::new(min.x(), min.y(), min.z()), dx, dz, material),              // bottom
+    ]
+}
\ No newline at end of file
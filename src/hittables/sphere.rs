@@ -1,11 +1,18 @@
-use super::hittable::{HitRecord, Hittable};
-use crate::vec3::{Vec3, Point3};
+use std::{f64::consts::PI, sync::Arc};
+
+use image::Rgb;
+
+use super::{hittable::{HitRecord, Hittable}, aabb::Aabb};
+use crate::vec3::{Vec3, Point3, sample_to_sphere};
+use crate::onb::Onb;
 use crate::ray::Ray;
+use crate::materials::{material::Material, lambertian::Lambertian};
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct Sphere {
     pub center: Point3,
-    pub radius: f64
+    pub radius: f64,
+    pub material: Material
 }
 
 impl Sphere {
@@ -13,14 +20,16 @@ impl Sphere {
     pub fn new_empty() -> Self {
         Self {
             center: Point3::new_empty(),
-            radius: 0.0
+            radius: 0.0,
+            material: Material::new(Arc::new(Lambertian::new(Rgb::from([0.5, 0.5, 0.5]))))
         }
     }
 
-    pub fn new(center: Point3, radius: f64) -> Self {
+    pub fn new(center: Point3, radius: f64, material: Material) -> Self {
         Self {
             center,
-            radius
+            radius,
+            material
         }
     }
 }
@@ -49,10 +58,58 @@ impl Hittable for Sphere {
 
         rec.t = root;
         rec.p = r.at(rec.t);
-        rec.normal = (rec.p - self.center) / self.radius;
         let outward_normal: Vec3 = (rec.p - self.center) / self.radius;
         rec.set_face_normal(r, outward_normal);
+        (rec.u, rec.v) = get_sphere_uv(&outward_normal);
+        rec.material = Some(self.material.clone());
 
         true
     }
+
+    fn bounding_box(&self) -> Aabb {
+        // Negative radii are used for hollow spheres, so only the magnitude matters here
+        let r: f64 = self.radius.abs();
+        Aabb::new(
+            self.center - Vec3::new(r, r, r),
+            self.center + Vec3::new(r, r, r)
+        )
+    }
+
+    // Samples the cone of directions the sphere covers as seen from origin, which
+    // only exists from outside the sphere
+    fn pdf_value(&self, origin: &Point3, direction: &Vec3) -> f64 {
+        let distance_squared: f64 = (self.center - *origin).length_squared();
+        if distance_squared <= self.radius*self.radius {
+            return 0.0;
+        }
+
+        let mut rec: HitRecord = HitRecord::new_empty();
+        if !self.hit(&Ray::new(*origin, *direction, 0.0), 0.0001, f64::INFINITY, &mut rec) {
+            return 0.0;
+        }
+
+        let cos_theta_max: f64 = (1.0 - self.radius*self.radius/distance_squared).sqrt();
+        let solid_angle: f64 = 2.0*PI*(1.0 - cos_theta_max);
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: &Point3, u: [f64; 2]) -> Vec3 {
+        let direction: Vec3 = self.center - *origin;
+        let distance_squared: f64 = direction.length_squared();
+        if distance_squared <= self.radius*self.radius {
+            return direction;
+        }
+
+        let uvw: Onb = Onb::build_from_w(direction);
+        uvw.local(sample_to_sphere(u, self.radius, distance_squared))
+    }
+}
+
+// Maps a point on the unit sphere to texture coordinates: u goes around the Y axis
+// starting from -X, and v goes from the bottom (Y = -1) to the top (Y = +1)
+pub fn get_sphere_uv(p: &Point3) -> (f64, f64) {
+    let theta: f64 = (-p.y()).acos();
+    let phi: f64 = (-p.z()).atan2(p.x()) + PI;
+
+    (phi / (2.0*PI), theta / PI)
 }
\ No newline at end of file
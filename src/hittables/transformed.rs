@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use super::{hittable::{HitRecord, Hittable}, aabb::Aabb};
+use crate::mat4::Mat4;
+use crate::vec3::{Point3, Vec3, F64Multiplier};
+use crate::ray::Ray;
+use crate::utils::clamp;
+
+// Instance of another hittable placed in the world by an affine transform. The
+// wrapped object can be shared between any number of instances.
+#[derive(Clone)]
+pub struct Transformed {
+    object: Arc<dyn Hittable + Send + Sync>,
+    object_to_world: Mat4,
+    world_to_object: Mat4,
+    // Normals transform by the inverse transpose to stay perpendicular to the surface
+    normal_to_world: Mat4,
+    motion: Option<Motion>,
+    bbox: Aabb,
+}
+
+// Straight line movement by translate between time0 and time1, on top of the
+// instance's transform
+#[derive(Clone, Copy, Debug)]
+pub struct Motion {
+    pub translate: Vec3,
+    pub time0: f64,
+    pub time1: f64,
+}
+
+impl Motion {
+    pub fn offset(&self, time: f64) -> Vec3 {
+        let fraction: f64 = clamp((time - self.time0) / (self.time1 - self.time0), 0.0, 1.0);
+        F64Multiplier(fraction) * self.translate
+    }
+}
+
+impl Transformed {
+    // Returns None if the transform can't be inverted, e.g. a scale by zero
+    pub fn new(object: Arc<dyn Hittable + Send + Sync>, object_to_world: Mat4) -> Option<Self> {
+        let world_to_object: Mat4 = object_to_world.inverse()?;
+        let bbox: Aabb = transform_box(&object.bounding_box(), &object_to_world);
+
+        Some(Self {
+            object,
+            object_to_world,
+            world_to_object,
+            normal_to_world: world_to_object.transpose(),
+            motion: None,
+            bbox
+        })
+    }
+
+    // Instance that moves over time, the bounding box covers its whole path
+    pub fn new_moving(object: Arc<dyn Hittable + Send + Sync>, object_to_world: Mat4, motion: Motion) -> Option<Self> {
+        let mut transformed: Transformed = Self::new(object, object_to_world)?;
+        let end_box: Aabb = transform_box(&transformed.bbox, &Mat4::translation(motion.translate));
+        transformed.bbox = Aabb::surrounding_box(&transformed.bbox, &end_box);
+        transformed.motion = Some(motion);
+        Some(transformed)
+    }
+}
+
+impl Hittable for Transformed {
+    fn hit (&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let offset: Vec3 = match &self.motion {
+            Some(motion) => motion.offset(r.time()),
+            None => Vec3::new_empty(),
+        };
+
+        // The direction isn't normalized so t is the same in both spaces
+        let object_ray: Ray = Ray::new(
+            self.world_to_object.transform_point(r.origin() - offset),
+            self.world_to_object.transform_vector(r.direction()),
+            r.time()
+        );
+
+        if !self.object.hit(&object_ray, t_min, t_max, rec) {
+            return false;
+        }
+
+        rec.p = self.object_to_world.transform_point(rec.p) + offset;
+        rec.normal = self.normal_to_world.transform_vector(rec.normal).unit_vector();
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bbox
+    }
+}
+
+// Box around the eight transformed corners of the given box
+pub fn transform_box(bbox: &Aabb, transform: &Mat4) -> Aabb {
+    if bbox.is_empty() {
+        return *bbox;
+    }
+
+    let mut result: Aabb = Aabb::new_empty();
+    for i in 0..8 {
+        let corner: Point3 = Point3::new(
+            if i & 1 == 0 { bbox.minimum.x() } else { bbox.maximum.x() },
+            if i & 2 == 0 { bbox.minimum.y() } else { bbox.maximum.y() },
+            if i & 4 == 0 { bbox.minimum.z() } else { bbox.maximum.z() }
+        );
+        let p: Point3 = transform.transform_point(corner);
+        result = Aabb::surrounding_box(&result, &Aabb::new(p, p));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        hittables::{aabb::Aabb, hittable::{Hittable, HitRecord}, sphere::Sphere},
+        mat4::Mat4,
+        ray::Ray,
+        vec3::{F64Multiplier, Point3, Vec3}};
+
+    use super::Transformed;
+
+    fn unit_sphere() -> Arc<Sphere> {
+        let mut sphere: Sphere = Sphere::new_empty();
+        sphere.center = Point3::new(0.0, 0.0, 0.0);
+        sphere.radius = 1.0;
+        Arc::new(sphere)
+    }
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        for axis in 0..3 {
+            assert!((a[axis] - b[axis]).abs() < 1e-9, "{} != {}", a, b);
+        }
+    }
+
+    // Stretched along X, the normal follows the gradient of x^2/4 + y^2 + z^2
+    // rather than the stretched sphere normal
+    #[test]
+    fn normals_stay_perpendicular_under_non_uniform_scale() {
+        let ellipsoid: Transformed = Transformed::new(unit_sphere(), Mat4::scaling(Vec3::new(2.0, 1.0, 1.0))).unwrap();
+        let theta: f64 = 60.0_f64.to_radians();
+        let p: Point3 = Point3::new(2.0*theta.cos(), theta.sin(), 0.0);
+        let expected: Vec3 = Vec3::new(p.x() / 4.0, p.y(), 0.0).unit_vector();
+
+        let ray: Ray = Ray::new(p + F64Multiplier(5.0)*expected, -expected, 0.0);
+        let mut rec: HitRecord = HitRecord::new_empty();
+        assert!(ellipsoid.hit(&ray, 0.0001, f64::INFINITY, &mut rec));
+
+        assert_near(rec.p, p);
+        assert_near(rec.normal, expected);
+        assert!(rec.front_face);
+    }
+
+    #[test]
+    fn bounding_box_covers_the_transformed_object() {
+        let transform: Mat4 = Mat4::translation(Vec3::new(5.0, 0.0, -1.0))
+            * Mat4::rotation(Vec3::new(0.0, 0.0, 1.0), 45.0)
+            * Mat4::scaling(Vec3::new(2.0, 1.0, 1.0));
+        let instance: Transformed = Transformed::new(unit_sphere(), transform).unwrap();
+        let bbox: Aabb = instance.bounding_box();
+
+        // The corners of the sphere's box, (+-2, +-1) after scaling, rotated 45 degrees
+        let extent: f64 = 3.0 / 2.0_f64.sqrt();
+        assert_near(bbox.minimum, Point3::new(5.0 - extent, -extent, -2.0));
+        assert_near(bbox.maximum, Point3::new(5.0 + extent, extent, 0.0));
+    }
+
+    #[test]
+    fn singular_transform_is_rejected() {
+        assert!(Transformed::new(unit_sphere(), Mat4::scaling(Vec3::new(0.0, 1.0, 1.0))).is_none());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,114 @@
+use super::{hittable::{HitRecord, Hittable}, aabb::Aabb};
+use crate::vec3::{Vec3, Point3, F64Multiplier};
+use crate::ray::Ray;
+use crate::materials::material::Material;
+
+// Padding for the bounding box so triangles lying in an axis plane still have volume
+const BOX_PADDING: f64 = 0.0001;
+const PARALLEL_EPSILON: f64 = 1e-12;
+
+#[derive(Clone)]
+pub struct Triangle {
+    pub vertices: [Point3; 3],
+    // Optional per-vertex normals for smooth shading
+    pub normals: Option<[Vec3; 3]>,
+    // Optional per-vertex texture coordinates, barycentric coordinates are used otherwise
+    pub uvs: Option<[(f64, f64); 3]>,
+    pub material: Material,
+}
+
+impl Triangle {
+    pub fn new(v0: Point3, v1: Point3, v2: Point3, material: Material) -> Self {
+        Self {
+            vertices: [v0, v1, v2],
+            normals: None,
+            uvs: None,
+            material
+        }
+    }
+
+    pub fn with_normals(mut self, normals: [Vec3; 3]) -> Self {
+        self.normals = Some(normals.map(|n| n.unit_vector()));
+        self
+    }
+
+    pub fn with_uvs(mut self, uvs: [(f64, f64); 3]) -> Self {
+        self.uvs = Some(uvs);
+        self
+    }
+}
+
+impl Hittable for Triangle {
+    // Möller–Trumbore ray-triangle intersection
+    fn hit (&self, r: &Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let [v0, v1, v2] = self.vertices;
+        let edge1: Vec3 = v1 - v0;
+        let edge2: Vec3 = v2 - v0;
+
+        let pvec: Vec3 = r.direction().cross(edge2);
+        let det: f64 = edge1.dot(pvec);
+        if det.abs() < PARALLEL_EPSILON {
+            return false;
+        }
+        let inv_det: f64 = 1.0 / det;
+
+        let tvec: Vec3 = r.origin() - v0;
+        let b1: f64 = tvec.dot(pvec) * inv_det;
+        if !(0.0..=1.0).contains(&b1) {
+            return false;
+        }
+
+        let qvec: Vec3 = tvec.cross(edge1);
+        let b2: f64 = r.direction().dot(qvec) * inv_det;
+        if b2 < 0.0 || b1 + b2 > 1.0 {
+            return false;
+        }
+
+        let t: f64 = edge2.dot(qvec) * inv_det;
+        if t < t_min || t > t_max {
+            return false;
+        }
+        let b0: f64 = 1.0 - b1 - b2;
+
+        rec.t = t;
+        rec.p = r.at(t);
+
+        // The geometric normal decides which side was hit, the shading normal is
+        // then flipped to match so smooth shading never points into the surface
+        let geometric_normal: Vec3 = edge1.cross(edge2).unit_vector();
+        rec.front_face = r.direction().dot(geometric_normal) < 0.0;
+        let shading_normal: Vec3 = match self.normals {
+            None => geometric_normal,
+            Some([n0, n1, n2]) => (F64Multiplier(b0)*n0 + F64Multiplier(b1)*n1 + F64Multiplier(b2)*n2).unit_vector(),
+        };
+        rec.normal = if rec.front_face { shading_normal } else { -shading_normal };
+
+        (rec.u, rec.v) = match self.uvs {
+            None => (b1, b2),
+            Some([uv0, uv1, uv2]) => (
+                b0*uv0.0 + b1*uv1.0 + b2*uv2.0,
+                b0*uv0.1 + b1*uv1.1 + b2*uv2.1
+            ),
+        };
+        rec.material = Some(self.material.clone());
+
+        true
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let [v0, v1, v2] = self.vertices;
+        let padding: Vec3 = Vec3::new(BOX_PADDING, BOX_PADDING, BOX_PADDING);
+        let minimum: Point3 = Point3::new(
+            v0.x().min(v1.x()).min(v2.x()),
+            v0.y().min(v1.y()).min(v2.y()),
+            v0.z().min(v1.z()).min(v2.z())
+        );
+        let maximum: Point3 = Point3::new(
+            v0.x().max(v1.x()).max(v2.x()),
+            v0.y().max(v1.y()).max(v2.y()),
+            v0.z().max(v1.z()).max(v2.z())
+        );
+
+        Aabb::new(minimum - padding, maximum + padding)
+    }
+}
\ No newline at end of file
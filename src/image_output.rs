@@ -0,0 +1,90 @@
+use std::{fs::File, io::{self, BufWriter, Write}, path::Path, fmt::{self, Display}, error::Error};
+
+use image::{Rgb, codecs::hdr::HdrEncoder};
+
+use crate::{framebuffer::FrameBuffer, render_settings::RenderSettings};
+
+#[derive(Debug)]
+pub enum OutputError {
+    Io(io::Error),
+    Image(image::ImageError),
+    Exr(exr::error::Error),
+}
+
+impl Display for OutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputError::Io(ex) => write!(f, "{}", ex),
+            OutputError::Image(ex) => write!(f, "{}", ex),
+            OutputError::Exr(ex) => write!(f, "{}", ex),
+        }
+    }
+}
+
+impl Error for OutputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            OutputError::Io(ex) => Some(ex),
+            OutputError::Image(ex) => Some(ex),
+            OutputError::Exr(ex) => Some(ex),
+        }
+    }
+}
+
+// Writes the framebuffer in the format given by the file extension. The high dynamic
+// range formats (.exr, .hdr and .pfm) get the linear values with no gamma correction,
+// anything else goes through the image crate as a gamma corrected 8 bit image.
+pub fn save_framebuffer(framebuffer: &FrameBuffer, out_file: &str, settings: &RenderSettings) -> Result<(), OutputError> {
+    let extension: String = Path::new(out_file)
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "exr" => save_exr(framebuffer, out_file),
+        "hdr" => save_hdr(framebuffer, out_file),
+        "pfm" => save_pfm(framebuffer, out_file),
+        _ => framebuffer.to_rgb_image(settings).save(out_file).map_err(OutputError::Image),
+    }
+}
+
+fn save_exr(framebuffer: &FrameBuffer, out_file: &str) -> Result<(), OutputError> {
+    exr::prelude::write_rgb_file(out_file, framebuffer.width() as usize, framebuffer.height() as usize, |x, y| {
+        let pixel: Rgb<f64> = framebuffer.get_pixel(x as u32, y as u32);
+        (pixel[0] as f32, pixel[1] as f32, pixel[2] as f32)
+    }).map_err(OutputError::Exr)
+}
+
+fn save_hdr(framebuffer: &FrameBuffer, out_file: &str) -> Result<(), OutputError> {
+    let mut pixels: Vec<Rgb<f32>> = Vec::with_capacity((framebuffer.width() * framebuffer.height()) as usize);
+    for y in 0..framebuffer.height() {
+        for x in 0..framebuffer.width() {
+            let pixel: Rgb<f64> = framebuffer.get_pixel(x, y);
+            // Radiance HDR can't store negative values
+            pixels.push(Rgb::from([pixel[0].max(0.0) as f32, pixel[1].max(0.0) as f32, pixel[2].max(0.0) as f32]));
+        }
+    }
+
+    let writer: BufWriter<File> = BufWriter::new(File::create(out_file).map_err(OutputError::Io)?);
+    HdrEncoder::new(writer)
+        .encode(&pixels, framebuffer.width() as usize, framebuffer.height() as usize)
+        .map_err(OutputError::Image)
+}
+
+// Portable float map: a small text header followed by raw 32 bit floats,
+// a negative scale marks them as little endian. Rows go from bottom to top.
+fn save_pfm(framebuffer: &FrameBuffer, out_file: &str) -> Result<(), OutputError> {
+    let mut writer: BufWriter<File> = BufWriter::new(File::create(out_file).map_err(OutputError::Io)?);
+
+    write!(writer, "PF\n{} {}\n-1.0\n", framebuffer.width(), framebuffer.height()).map_err(OutputError::Io)?;
+    for y in (0..framebuffer.height()).rev() {
+        for x in 0..framebuffer.width() {
+            let pixel: Rgb<f64> = framebuffer.get_pixel(x, y);
+            for channel in pixel.0 {
+                writer.write_all(&(channel as f32).to_le_bytes()).map_err(OutputError::Io)?;
+            }
+        }
+    }
+
+    writer.flush().map_err(OutputError::Io)
+}
\ No newline at end of file
@@ -0,0 +1,36 @@
+// Path tracer library, the command line renderer in main.rs is built on top of it.
+//
+// Load or build a Scene, pick the RenderSettings and hand both to a Renderer,
+// which returns the image as a FrameBuffer of linear colors.
+pub mod vec3;
+pub mod mat4;
+pub mod onb;
+pub mod pdfs;
+pub mod samplers;
+pub mod microfacet;
+pub mod color;
+pub mod ray;
+pub mod hittables;
+pub mod utils;
+pub mod camera;
+pub mod materials;
+pub mod render_image;
+pub mod render_error;
+pub mod rgb_wrapper;
+pub mod backgrounds;
+pub mod scene;
+pub mod render_settings;
+pub mod tile_scheduler;
+pub mod framebuffer;
+pub mod accumulator;
+pub mod image_output;
+pub mod textures;
+
+pub use camera::Camera;
+pub use framebuffer::FrameBuffer;
+pub use hittables::hittable::Hittable;
+pub use materials::material::Material;
+pub use render_error::RenderError;
+pub use render_image::Renderer;
+pub use render_settings::RenderSettings;
+pub use scene::scene_loader::Scene;
\ No newline at end of file
@@ -1,21 +1,139 @@
 mod arguments;
-mod vec3;
-mod color;
-mod ray;
-mod hittables;
-mod utils;
-mod camera;
-mod materials;
-mod render_image;
-mod rgb_wrapper;
+
+use std::{process::ExitCode, sync::Arc};
+
+use image::Rgb;
+
+use rust_ray_tracer::{
+    Renderer,
+    RenderError,
+    RenderSettings,
+    Scene,
+    backgrounds::constant::Constant,
+    accumulator::Accumulator,
+    image_output::{OutputError, save_framebuffer},
+    render_image::StopReason,
+    scene::scene_loader::{default_scene, load_scene, validate_camera, apply_render_description}};
 
 use arguments::{Args, parse_command_line_args};
-use render_image::render_image;
 
-fn main() {
+fn main() -> ExitCode {
     let args: Args = parse_command_line_args();
-    
-    let aspect_ratio: f64 = args.numerator_ar / args.denominator_ar;
 
-    render_image(args.out_file, args.image_width, aspect_ratio, args.multithread)
+    match run(args) {
+        Err(ex) => {
+            eprintln!("{}", ex);
+            ExitCode::FAILURE
+        }
+        Ok(()) => ExitCode::SUCCESS,
+    }
+}
+
+fn run(args: Args) -> Result<(), RenderError> {
+    let mut scene: Scene = match &args.scene {
+        None => default_scene(),
+        Some(scene_file) => load_scene(scene_file)?,
+    };
+
+    // Command line arguments take precedence over the scene file
+    if let Some(look_from) = args.look_from { scene.camera.look_from = look_from.into(); }
+    if let Some(look_at) = args.look_at { scene.camera.look_at = look_at.into(); }
+    if let Some(vup) = args.vup { scene.camera.vup = vup.into(); }
+    if let Some(vfov) = args.vfov { scene.camera.vfov = vfov; }
+    if let Some(aperture) = args.aperture { scene.camera.aperture = aperture; }
+    if args.focus_dist.is_some() { scene.camera.focus_dist = args.focus_dist; }
+    if let Some(shutter_open) = args.shutter_open { scene.camera.shutter_open = shutter_open; }
+    if let Some(shutter_close) = args.shutter_close { scene.camera.shutter_close = shutter_close; }
+    if let Some(color) = args.background {
+        scene.background = Arc::new(Constant::new(Rgb::from(<[f64; 3]>::from(color))));
+    }
+    if let Err((field, message)) = validate_camera(&scene.camera) {
+        return Err(RenderError::Camera { field, message });
+    }
+
+    let settings: RenderSettings = render_settings(&args, &scene);
+    settings.validate()?;
+
+    let multithread: bool = args.multithread || args.threads.is_some();
+    let accumulator: Accumulator = if args.progressive || args.time_limit.is_some() || args.noise_threshold.is_some() {
+        render_progressive(args.out_file, &scene, &settings, multithread, args.write_interval)?
+    } else {
+        render_image(args.out_file, &scene, &settings, multithread)?
+    };
+
+    if let Some(heatmap_file) = args.spp_heatmap {
+        if let Err(ex) = accumulator.sample_heatmap().save(&heatmap_file) {
+            return Err(RenderError::Output { file: heatmap_file, source: OutputError::Image(ex) });
+        }
+        println!("Samples per pixel heatmap saved to file: {}", heatmap_file);
+    }
+    Ok(())
+}
+
+// Renders the scene and saves it to out_file, printing progress along the way
+fn render_image(out_file: String, scene: &Scene, settings: &RenderSettings, mt: bool) -> Result<Accumulator, RenderError> {
+    let renderer: Renderer = Renderer::new(scene, *settings).show_progress(true);
+    let accumulator: Accumulator = renderer.render_samples(mt)?;
+
+    match save_framebuffer(&accumulator.to_framebuffer(), &out_file, settings) {
+        Err(ex) => Err(RenderError::Output { file: out_file, source: ex }),
+        Ok(_) => {
+            println!("\nImage saved to file: {}", out_file);
+            Ok(accumulator)
+        }
+    }
+}
+
+// Renders the scene in passes, writing the image so far every write_interval
+// seconds and once more at the end
+fn render_progressive(out_file: String, scene: &Scene, settings: &RenderSettings, mt: bool, write_interval: f64) -> Result<Accumulator, RenderError> {
+    let renderer: Renderer = Renderer::new(scene, *settings).show_progress(true);
+
+    let mut last_write: f64 = 0.0;
+    let mut stop: Option<StopReason> = None;
+    let accumulator: Accumulator = renderer.render_progressive(mt, |accumulator, report| {
+        stop = report.stop;
+        let elapsed: f64 = report.elapsed.as_secs_f64();
+        if report.stop.is_none() && elapsed - last_write < write_interval {
+            return Ok(());
+        }
+        last_write = elapsed;
+
+        match save_framebuffer(&accumulator.to_framebuffer(), &out_file, settings) {
+            Err(ex) => Err(RenderError::Output { file: out_file.clone(), source: ex }),
+            Ok(_) => Ok(()),
+        }
+    })?;
+
+    match stop {
+        Some(StopReason::TimeLimit) => println!("\nStopped at the time limit"),
+        Some(StopReason::NoiseThreshold) => println!("\nStopped at the noise threshold"),
+        _ => println!("\nStopped once no pixel needed more samples"),
+    }
+    println!("Image saved to file: {}", out_file);
+    Ok(accumulator)
 }
+
+fn render_settings(args: &Args, scene: &Scene) -> RenderSettings {
+    let mut settings: RenderSettings = RenderSettings::default();
+    apply_render_description(&mut settings, &scene.render);
+
+    if let Some(image_width) = args.image_width { settings.image_width = image_width; }
+    if let (Some(numerator), Some(denominator)) = (args.numerator_ar, args.denominator_ar) {
+        settings.aspect_ratio = numerator / denominator;
+    }
+    if let Some(samples_per_pixel) = args.samples_per_pixel { settings.samples_per_pixel = samples_per_pixel; }
+    if let Some(max_depth) = args.max_depth { settings.max_depth = max_depth; }
+    if let Some(gamma) = args.gamma { settings.gamma = gamma; }
+    if args.threads.is_some() { settings.threads = args.threads; }
+    if let Some(tile_size) = args.tile_size { settings.tile_size = tile_size; }
+    if let Some(seed) = args.seed { settings.seed = seed; }
+    if let Some(sampler) = args.sampler { settings.sampler = sampler; }
+    if let Some(samples_per_pass) = args.samples_per_pass { settings.samples_per_pass = samples_per_pass; }
+    if args.time_limit.is_some() { settings.time_limit = args.time_limit; }
+    if args.noise_threshold.is_some() { settings.noise_threshold = args.noise_threshold; }
+    if args.adaptive_threshold.is_some() { settings.adaptive_threshold = args.adaptive_threshold; }
+    if let Some(adaptive_min_samples) = args.adaptive_min_samples { settings.adaptive_min_samples = adaptive_min_samples; }
+
+    settings
+}
\ No newline at end of file
@@ -0,0 +1,211 @@
+use std::ops::Mul;
+
+use crate::vec3::{Point3, Vec3};
+
+// 4x4 affine transformation matrix in row-major order, applied to column vectors
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mat4 {
+    m: [[f64; 4]; 4],
+}
+
+impl Mat4 {
+    pub fn identity() -> Self {
+        Self {
+            m: [
+                [1.0, 0.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0, 0.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0]
+            ]
+        }
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        let mut result: Mat4 = Self::identity();
+        result.m[0][3] = offset.x();
+        result.m[1][3] = offset.y();
+        result.m[2][3] = offset.z();
+        result
+    }
+
+    pub fn scaling(factors: Vec3) -> Self {
+        let mut result: Mat4 = Self::identity();
+        result.m[0][0] = factors.x();
+        result.m[1][1] = factors.y();
+        result.m[2][2] = factors.z();
+        result
+    }
+
+    // Counter-clockwise rotation by the given angle about an axis through the origin
+    pub fn rotation(axis: Vec3, degrees: f64) -> Self {
+        let a: Vec3 = axis.unit_vector();
+        let (x, y, z) = (a.x(), a.y(), a.z());
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let t: f64 = 1.0 - cos;
+
+        Self {
+            m: [
+                [t*x*x + cos,   t*x*y - sin*z, t*x*z + sin*y, 0.0],
+                [t*x*y + sin*z, t*y*y + cos,   t*y*z - sin*x, 0.0],
+                [t*x*z - sin*y, t*y*z + sin*x, t*z*z + cos,   0.0],
+                [0.0,           0.0,           0.0,           1.0]
+            ]
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut result: Mat4 = *self;
+        for (i, row) in result.m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        result
+    }
+
+    // Gauss-Jordan elimination with partial pivoting, None for singular matrices
+    pub fn inverse(&self) -> Option<Self> {
+        let mut a: [[f64; 4]; 4] = self.m;
+        let mut inv: [[f64; 4]; 4] = Self::identity().m;
+
+        for col in 0..4 {
+            let mut pivot: usize = col;
+            for row in (col + 1)..4 {
+                if a[row][col].abs() > a[pivot][col].abs() {
+                    pivot = row;
+                }
+            }
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale: f64 = 1.0 / a[col][col];
+            for j in 0..4 {
+                a[col][j] *= scale;
+                inv[col][j] *= scale;
+            }
+
+            for row in 0..4 {
+                if row != col {
+                    let factor: f64 = a[row][col];
+                    for j in 0..4 {
+                        a[row][j] -= factor * a[col][j];
+                        inv[row][j] -= factor * inv[col][j];
+                    }
+                }
+            }
+        }
+
+        Some(Self {
+            m: inv
+        })
+    }
+
+    pub fn transform_point(&self, p: Point3) -> Point3 {
+        let m = &self.m;
+        let x: f64 = m[0][0]*p.x() + m[0][1]*p.y() + m[0][2]*p.z() + m[0][3];
+        let y: f64 = m[1][0]*p.x() + m[1][1]*p.y() + m[1][2]*p.z() + m[1][3];
+        let z: f64 = m[2][0]*p.x() + m[2][1]*p.y() + m[2][2]*p.z() + m[2][3];
+        let w: f64 = m[3][0]*p.x() + m[3][1]*p.y() + m[3][2]*p.z() + m[3][3];
+
+        if w == 1.0 { Point3::new(x, y, z) } else { Point3::new(x / w, y / w, z / w) }
+    }
+
+    // Directions ignore the translation part of the matrix
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0]*v.x() + m[0][1]*v.y() + m[0][2]*v.z(),
+            m[1][0]*v.x() + m[1][1]*v.y() + m[1][2]*v.z(),
+            m[2][0]*v.x() + m[2][1]*v.y() + m[2][2]*v.z()
+        )
+    }
+}
+
+impl Mul for Mat4 {
+    type Output = Self;
+
+    fn mul(self, _rhs: Self) -> Self {
+        let mut result: [[f64; 4]; 4] = [[0.0; 4]; 4];
+        for (i, row) in result.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * _rhs.m[k][j]).sum();
+            }
+        }
+
+        Self {
+            m: result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vec3::{Point3, Vec3};
+
+    use super::Mat4;
+
+    fn assert_near(a: &Mat4, b: &Mat4) {
+        for i in 0..4 {
+            for j in 0..4 {
+                assert!((a.m[i][j] - b.m[i][j]).abs() < 1e-9, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
+    fn assert_near_vector(a: Vec3, b: Vec3) {
+        for axis in 0..3 {
+            assert!((a[axis] - b[axis]).abs() < 1e-9, "{} != {}", a, b);
+        }
+    }
+
+    fn composite() -> Mat4 {
+        Mat4::translation(Vec3::new(1.0, -2.0, 3.0))
+            * Mat4::rotation(Vec3::new(1.0, 1.0, 0.5), 37.0)
+            * Mat4::scaling(Vec3::new(2.0, 0.5, 3.0))
+    }
+
+    #[test]
+    fn inverse_times_matrix_is_identity() {
+        let m: Mat4 = composite();
+        let inverse: Mat4 = m.inverse().expect("the matrix is invertible");
+
+        assert_near(&(inverse * m), &Mat4::identity());
+        assert_near(&(m * inverse), &Mat4::identity());
+    }
+
+    #[test]
+    fn singular_matrix_has_no_inverse() {
+        assert!(Mat4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse().is_none());
+    }
+
+    #[test]
+    fn transpose_swaps_rows_and_columns() {
+        let m: Mat4 = composite();
+        for i in 0..4 {
+            for j in 0..4 {
+                assert_eq!(m.transpose().m[i][j], m.m[j][i]);
+            }
+        }
+        assert_eq!(m.transpose().transpose(), m);
+    }
+
+    #[test]
+    fn rotation_is_counter_clockwise() {
+        let quarter_turn: Mat4 = Mat4::rotation(Vec3::new(0.0, 0.0, 1.0), 90.0);
+
+        assert_near_vector(quarter_turn.transform_vector(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(0.0, 1.0, 0.0));
+        assert_near_vector(quarter_turn.transform_vector(Vec3::new(0.0, 1.0, 0.0)), Vec3::new(-1.0, 0.0, 0.0));
+        assert_near(&(quarter_turn * quarter_turn.transpose()), &Mat4::identity());
+    }
+
+    #[test]
+    fn vectors_ignore_translation() {
+        let m: Mat4 = Mat4::translation(Vec3::new(1.0, 2.0, 3.0));
+
+        assert_near_vector(m.transform_point(Point3::new(1.0, 1.0, 1.0)), Point3::new(2.0, 3.0, 4.0));
+        assert_near_vector(m.transform_vector(Vec3::new(1.0, 1.0, 1.0)), Vec3::new(1.0, 1.0, 1.0));
+    }
+}
\ No newline at end of file
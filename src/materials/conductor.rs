@@ -0,0 +1,189 @@
+use image::Rgb;
+use serde::Deserialize;
+
+use crate::ray::Ray;
+use crate::hittables::hittable::HitRecord;
+use crate::rgb_wrapper::RgbWrapper;
+use crate::vec3::{Vec3, reflect};
+use crate::microfacet::{MIN_ALPHA, ggx_d, smith_g2, fresnel_conductor};
+use crate::pdfs::ggx_pdf::GgxPdf;
+use crate::utils::RenderRng;
+
+use super::material::{Scatter, ScatterRecord};
+
+// Measured complex indices of refraction at the red, green and blue wavelengths
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConductorPreset {
+    Gold,
+    Copper,
+    Aluminium,
+    Silver,
+}
+
+impl ConductorPreset {
+    // (eta, k) per channel
+    pub fn ior(&self) -> ([f64; 3], [f64; 3]) {
+        match self {
+            ConductorPreset::Gold => ([0.143, 0.374, 1.442], [3.983, 2.385, 1.603]),
+            ConductorPreset::Copper => ([0.200, 0.924, 1.102], [3.912, 2.452, 2.142]),
+            ConductorPreset::Aluminium => ([1.657, 0.880, 0.521], [9.224, 6.270, 4.837]),
+            ConductorPreset::Silver => ([0.155, 0.117, 0.138], [4.828, 3.122, 2.147]),
+        }
+    }
+}
+
+// Metal with a GGX microfacet distribution and the Fresnel reflectance of its
+// complex index of refraction eta + i k. A roughness of zero is a perfect mirror.
+#[derive(Clone, Copy)]
+pub struct Conductor {
+    pub eta: [f64; 3],
+    pub k: [f64; 3],
+    // Perceptual roughness between 0 and 1, squared to get the GGX alpha
+    pub roughness: f64,
+}
+
+impl Conductor {
+    pub fn new(eta: [f64; 3], k: [f64; 3], roughness: f64) -> Self {
+        Self {
+            eta,
+            k,
+            roughness
+        }
+    }
+
+    pub fn from_preset(preset: ConductorPreset, roughness: f64) -> Self {
+        let (eta, k) = preset.ior();
+        Self::new(eta, k, roughness)
+    }
+
+    fn alpha(&self) -> f64 {
+        self.roughness*self.roughness
+    }
+
+    fn fresnel(&self, cos_theta: f64) -> RgbWrapper {
+        RgbWrapper(Rgb::from([
+            fresnel_conductor(cos_theta, self.eta[0], self.k[0]),
+            fresnel_conductor(cos_theta, self.eta[1], self.k[1]),
+            fresnel_conductor(cos_theta, self.eta[2], self.k[2])
+        ]))
+    }
+}
+
+impl Scatter for Conductor {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, _rng: &mut RenderRng) -> Option<ScatterRecord> {
+        let wo: Vec3 = -r_in.direction().unit_vector();
+
+        if self.alpha() < MIN_ALPHA {
+            let reflected: Vec3 = reflect(&(-wo), &rec.normal);
+            return Some(ScatterRecord::Specular {
+                attenuation: self.fresnel(wo.dot(rec.normal)),
+                ray: Ray::new(rec.p, reflected, r_in.time())
+            });
+        }
+
+        Some(ScatterRecord::Sampled { pdf: Box::new(GgxPdf::new(rec.normal, wo, self.alpha())) })
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> RgbWrapper {
+        let black: RgbWrapper = RgbWrapper(Rgb::from([0.0, 0.0, 0.0]));
+        let wo: Vec3 = -r_in.direction().unit_vector();
+        let wi: Vec3 = scattered.direction().unit_vector();
+        let cos_o: f64 = wo.dot(rec.normal);
+        let cos_i: f64 = wi.dot(rec.normal);
+        if cos_o <= 0.0 || cos_i <= 0.0 || self.alpha() < MIN_ALPHA {
+            return black;
+        }
+
+        let h: Vec3 = (wo + wi).unit_vector();
+        let d: f64 = ggx_d(h.dot(rec.normal), self.alpha());
+        let g: f64 = smith_g2(cos_o, cos_i, self.alpha());
+
+        // D G F / (4 cos_o cos_i), times cos_i
+        self.fresnel(wi.dot(h)) * (d*g / (4.0*cos_o))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittables::hittable::HitRecord,
+        materials::material::Scatter,
+        pdfs::{ggx_pdf::GgxPdf, pdf::Pdf},
+        ray::Ray,
+        utils::new_rng,
+        vec3::Vec3};
+
+    use super::Conductor;
+
+    // Points u along each side of the unit square
+    const SAMPLES: usize = 256;
+
+    fn surface() -> HitRecord {
+        let mut rec: HitRecord = HitRecord::new_empty();
+        rec.normal = Vec3::new(0.0, 0.0, 1.0);
+        rec.front_face = true;
+        rec
+    }
+
+    // Light from wi reflected towards wo
+    fn eval(conductor: &Conductor, wo: Vec3, wi: Vec3) -> f64 {
+        let rec: HitRecord = surface();
+        let r_in: Ray = Ray::new(rec.p + wo, -wo, 0.0);
+        conductor.eval(&r_in, &rec, &Ray::new(rec.p, wi, 0.0)).0.0[0]
+    }
+
+    // Fraction of the light coming from all directions that is reflected
+    // towards wo, importance sampled over an even grid of points u
+    fn albedo(conductor: &Conductor, wo: Vec3) -> f64 {
+        let pdf: GgxPdf = GgxPdf::new(surface().normal, wo, conductor.alpha());
+        let mut rng = new_rng(0, 0);
+        let mut sum: f64 = 0.0;
+        for i in 0..SAMPLES {
+            for j in 0..SAMPLES {
+                let u: [f64; 2] = [(i as f64 + 0.5) / SAMPLES as f64, (j as f64 + 0.5) / SAMPLES as f64];
+                let wi: Vec3 = pdf.generate(u, &mut rng);
+                let density: f64 = pdf.value(&wi);
+                if density > 0.0 {
+                    sum += eval(conductor, wo, wi) / density;
+                }
+            }
+        }
+        sum / (SAMPLES*SAMPLES) as f64
+    }
+
+    #[test]
+    fn eval_is_reciprocal() {
+        // eval includes the cosine of wi, the BRDF itself is the same both ways
+        let conductor: Conductor = Conductor::new([0.2, 0.9, 1.1], [3.9, 2.5, 2.1], 0.6);
+        let directions: [Vec3; 4] = [Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.3, 0.2, 0.9), Vec3::new(-0.5, 0.1, 0.4), Vec3::new(0.9, -0.4, 0.1)];
+        for wo in directions {
+            for wi in directions {
+                let forward: f64 = eval(&conductor, wo, wi) / wi.unit_vector().z();
+                let backward: f64 = eval(&conductor, wi, wo) / wo.unit_vector().z();
+                assert!(forward > 0.0 && (forward - backward).abs() < 1e-9*forward, "{} != {}", forward, backward);
+            }
+        }
+    }
+
+    #[test]
+    fn white_furnace_reflects_no_more_than_comes_in() {
+        // A perfect mirror at every microfacet, with a roughness of one. Light
+        // bouncing more than once between the microfacets isn't modelled, so
+        // some of it is lost
+        let conductor: Conductor = Conductor::new([1.0; 3], [1e4; 3], 1.0);
+        for wo in [Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.5, 0.2, 0.8), Vec3::new(1.0, 0.0, 0.2)] {
+            let albedo: f64 = albedo(&conductor, wo.unit_vector());
+            assert!(albedo <= 1.0, "{}", albedo);
+        }
+    }
+
+    #[test]
+    fn white_furnace_loses_little_on_smooth_surfaces() {
+        let conductor: Conductor = Conductor::new([1.0; 3], [1e4; 3], 0.3);
+        for wo in [Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.5, 0.2, 0.8)] {
+            let albedo: f64 = albedo(&conductor, wo.unit_vector());
+            assert!(albedo <= 1.0 && albedo > 0.95, "{}", albedo);
+        }
+    }
+}
\ No newline at end of file
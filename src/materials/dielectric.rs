@@ -0,0 +1,51 @@
+use image::Rgb;
+
+use crate::ray::Ray;
+use crate::hittables::hittable::HitRecord;
+use crate::rgb_wrapper::RgbWrapper;
+use crate::utils::{RenderRng, random_double};
+use crate::vec3::{Vec3, reflect, refract};
+
+use super::material::{Scatter, ScatterRecord};
+
+#[derive(Clone, Copy)]
+pub struct Dielectric {
+    pub ir: f64,
+}
+
+impl Dielectric {
+    pub fn new(index_of_refraction: f64) -> Self {
+        Self {
+            ir: index_of_refraction
+        }
+    }
+
+    // Schlick's approximation for reflectance
+    fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
+        let mut r0: f64 = (1.0 - ref_idx) / (1.0 + ref_idx);
+        r0 = r0*r0;
+        r0 + (1.0 - r0)*(1.0 - cosine).powi(5)
+    }
+}
+
+impl Scatter for Dielectric {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, rng: &mut RenderRng) -> Option<ScatterRecord> {
+        let refraction_ratio: f64 = if rec.front_face { 1.0 / self.ir } else { self.ir };
+
+        let unit_direction: Vec3 = r_in.direction().unit_vector();
+        let cos_theta: f64 = (-unit_direction).dot(rec.normal).min(1.0);
+        let sin_theta: f64 = (1.0 - cos_theta*cos_theta).sqrt();
+
+        let cannot_refract: bool = refraction_ratio * sin_theta > 1.0;
+        let direction: Vec3 = if cannot_refract || Self::reflectance(cos_theta, refraction_ratio) > random_double(rng) {
+            reflect(&unit_direction, &rec.normal)
+        } else {
+            refract(&unit_direction, &rec.normal, refraction_ratio)
+        };
+
+        Some(ScatterRecord::Specular {
+            attenuation: RgbWrapper(Rgb::from([1.0, 1.0, 1.0])),
+            ray: Ray::new(rec.p, direction, r_in.time())
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use image::Rgb;
+
+use crate::ray::Ray;
+use crate::hittables::hittable::HitRecord;
+use crate::rgb_wrapper::RgbWrapper;
+use crate::textures::{texture::Texture, solid_color::SolidColor};
+use crate::utils::RenderRng;
+
+use super::material::{Scatter, ScatterRecord};
+
+// Emits light from the front face of whatever it is applied to and reflects nothing
+#[derive(Clone)]
+pub struct DiffuseLight {
+    pub emit: Arc<dyn Texture + Send + Sync>,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Rgb<f64>) -> Self {
+        Self {
+            emit: Arc::new(SolidColor::new(emit))
+        }
+    }
+
+    pub fn new_textured(emit: Arc<dyn Texture + Send + Sync>) -> Self {
+        Self {
+            emit
+        }
+    }
+}
+
+impl Scatter for DiffuseLight {
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord, _rng: &mut RenderRng) -> Option<ScatterRecord> {
+        None
+    }
+
+    fn emitted(&self, _r_in: &Ray, rec: &HitRecord) -> RgbWrapper {
+        if rec.front_face {
+            self.emit.value(rec.u, rec.v, &rec.p)
+        } else {
+            RgbWrapper(Rgb::from([0.0, 0.0, 0.0]))
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use image::Rgb;
+
+use crate::ray::Ray;
+use crate::hittables::hittable::HitRecord;
+use crate::rgb_wrapper::RgbWrapper;
+use crate::utils::{RenderRng, clamp};
+use crate::pdfs::henyey_greenstein_pdf::{HenyeyGreensteinPdf, phase};
+use crate::textures::{texture::Texture, solid_color::SolidColor};
+
+use super::material::{Scatter, ScatterRecord};
+
+// Henyey-Greenstein phase function for participating media. The asymmetry g goes
+// from -1 (everything scattered back) through 0 (isotropic) to 1 (everything
+// scattered forward); fog and haze are typically somewhere around 0.7 to 0.9.
+#[derive(Clone)]
+pub struct HenyeyGreenstein {
+    pub albedo: Arc<dyn Texture + Send + Sync>,
+    pub g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Rgb<f64>, g: f64) -> Self {
+        Self::new_textured(Arc::new(SolidColor::new(albedo)), g)
+    }
+
+    pub fn new_textured(albedo: Arc<dyn Texture + Send + Sync>, g: f64) -> Self {
+        Self {
+            albedo,
+            // The sampling formula blows up at exactly +-1
+            g: clamp(g, -0.999, 0.999)
+        }
+    }
+}
+
+impl Scatter for HenyeyGreenstein {
+    fn scatter(&self, r_in: &Ray, _rec: &HitRecord, _rng: &mut RenderRng) -> Option<ScatterRecord> {
+        Some(ScatterRecord::Sampled { pdf: Box::new(HenyeyGreensteinPdf::new(r_in.direction(), self.g)) })
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> RgbWrapper {
+        let cos_theta: f64 = r_in.direction().unit_vector().dot(scattered.direction().unit_vector());
+        self.albedo.value(rec.u, rec.v, &rec.p) * phase(cos_theta, self.g)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,42 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use image::Rgb;
+
+use crate::ray::Ray;
+use crate::hittables::hittable::HitRecord;
+use crate::rgb_wrapper::RgbWrapper;
+use crate::pdfs::sphere_pdf::SpherePdf;
+use crate::textures::{texture::Texture, solid_color::SolidColor};
+use crate::utils::RenderRng;
+
+use super::material::{Scatter, ScatterRecord};
+
+// Phase function of a participating medium that scatters equally in all directions
+#[derive(Clone)]
+pub struct Isotropic {
+    pub albedo: Arc<dyn Texture + Send + Sync>,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Rgb<f64>) -> Self {
+        Self {
+            albedo: Arc::new(SolidColor::new(albedo))
+        }
+    }
+
+    pub fn new_textured(albedo: Arc<dyn Texture + Send + Sync>) -> Self {
+        Self {
+            albedo
+        }
+    }
+}
+
+impl Scatter for Isotropic {
+    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord, _rng: &mut RenderRng) -> Option<ScatterRecord> {
+        Some(ScatterRecord::Sampled { pdf: Box::new(SpherePdf) })
+    }
+
+    fn eval(&self, _r_in: &Ray, rec: &HitRecord, _scattered: &Ray) -> RgbWrapper {
+        self.albedo.value(rec.u, rec.v, &rec.p) * (1.0 / (4.0*PI))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,42 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use image::Rgb;
+
+use crate::ray::Ray;
+use crate::hittables::hittable::HitRecord;
+use crate::rgb_wrapper::RgbWrapper;
+use crate::pdfs::cosine_pdf::CosinePdf;
+use crate::textures::{texture::Texture, solid_color::SolidColor};
+use crate::utils::RenderRng;
+
+use super::material::{Scatter, ScatterRecord};
+
+#[derive(Clone)]
+pub struct Lambertian {
+    pub albedo: Arc<dyn Texture + Send + Sync>,
+}
+
+impl Lambertian {
+    pub fn new(albedo: Rgb<f64>) -> Self {
+        Self {
+            albedo: Arc::new(SolidColor::new(albedo))
+        }
+    }
+
+    pub fn new_textured(albedo: Arc<dyn Texture + Send + Sync>) -> Self {
+        Self {
+            albedo
+        }
+    }
+}
+
+impl Scatter for Lambertian {
+    fn scatter(&self, _r_in: &Ray, rec: &HitRecord, _rng: &mut RenderRng) -> Option<ScatterRecord> {
+        Some(ScatterRecord::Sampled { pdf: Box::new(CosinePdf::new(rec.normal)) })
+    }
+
+    fn eval(&self, _r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> RgbWrapper {
+        let cosine: f64 = rec.normal.dot(scattered.direction().unit_vector()).max(0.0);
+        self.albedo.value(rec.u, rec.v, &rec.p) * (cosine / PI)
+    }
+}
\ No newline at end of file
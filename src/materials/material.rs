@@ -1,19 +1,62 @@
-use std::rc::Rc;
+use std::sync::Arc;
+
+use image::Rgb;
 
 use crate::ray::Ray;
 use crate::hittables::hittable::HitRecord;
 use crate::rgb_wrapper::RgbWrapper;
+use crate::pdfs::pdf::Pdf;
+use crate::utils::RenderRng;
+
+// How a material scatters an incoming ray
+pub enum ScatterRecord {
+    // Mirror-like scattering in a single direction, which has no density and
+    // can't be lit by sampling lights
+    Specular { attenuation: RgbWrapper, ray: Ray },
+    // Scattering into many directions, importance sampled with pdf. The weight of
+    // a direction comes from Scatter::eval.
+    Sampled { pdf: Box<dyn Pdf> },
+}
 
 pub trait Scatter {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord, attenuation: &mut RgbWrapper, scattered: &mut Ray) -> bool;
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, rng: &mut RenderRng) -> Option<ScatterRecord>;
+
+    // BSDF times the cosine of the angle to the normal (the phase function for
+    // media) for light arriving along scattered and leaving along r_in. Only
+    // needed for materials that return ScatterRecord::Sampled.
+    fn eval(&self, _r_in: &Ray, _rec: &HitRecord, _scattered: &Ray) -> RgbWrapper {
+        RgbWrapper(Rgb::from([0.0, 0.0, 0.0]))
+    }
+
+    // Light given off by the surface towards the incoming ray, nothing by default
+    fn emitted(&self, _r_in: &Ray, _rec: &HitRecord) -> RgbWrapper {
+        RgbWrapper(Rgb::from([0.0, 0.0, 0.0]))
+    }
 }
 
+#[derive(Clone)]
 pub struct Material {
-    pub mat_type: Rc<dyn Scatter>,
+    pub mat_type: Arc<dyn Scatter + Send + Sync>,
+}
+
+impl Material {
+    pub fn new(mat_type: Arc<dyn Scatter + Send + Sync>) -> Self {
+        Self {
+            mat_type
+        }
+    }
 }
 
 impl Scatter for Material {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord, attenuation: &mut RgbWrapper, scattered: &mut Ray) -> bool {
-        self.mat_type.scatter(r_in, rec, attenuation, scattered)
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, rng: &mut RenderRng) -> Option<ScatterRecord> {
+        self.mat_type.scatter(r_in, rec, rng)
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> RgbWrapper {
+        self.mat_type.eval(r_in, rec, scattered)
+    }
+
+    fn emitted(&self, r_in: &Ray, rec: &HitRecord) -> RgbWrapper {
+        self.mat_type.emitted(r_in, rec)
     }
 }
\ No newline at end of file
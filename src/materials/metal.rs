@@ -0,0 +1,36 @@
+use image::Rgb;
+
+use crate::ray::Ray;
+use crate::hittables::hittable::HitRecord;
+use crate::rgb_wrapper::RgbWrapper;
+use crate::vec3::{Vec3, F64Multiplier, reflect, random_in_unit_sphere};
+use crate::utils::RenderRng;
+
+use super::material::{Scatter, ScatterRecord};
+
+#[derive(Clone, Copy)]
+pub struct Metal {
+    pub albedo: RgbWrapper,
+    pub fuzz: f64,
+}
+
+impl Metal {
+    pub fn new(albedo: Rgb<f64>, fuzz: f64) -> Self {
+        Self {
+            albedo: RgbWrapper(albedo),
+            fuzz: if fuzz < 1.0 { fuzz } else { 1.0 }
+        }
+    }
+}
+
+impl Scatter for Metal {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, rng: &mut RenderRng) -> Option<ScatterRecord> {
+        let reflected: Vec3 = reflect(&r_in.direction().unit_vector(), &rec.normal);
+        let scattered: Ray = Ray::new(rec.p, reflected + F64Multiplier(self.fuzz)*random_in_unit_sphere(rng), r_in.time());
+        if scattered.direction().dot(rec.normal) <= 0.0 {
+            return None;
+        }
+
+        Some(ScatterRecord::Specular { attenuation: self.albedo, ray: scattered })
+    }
+}
\ No newline at end of file
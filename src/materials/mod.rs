@@ -1 +1,10 @@
-pub mod material;
\ No newline at end of file
+pub mod material;
+pub mod lambertian;
+pub mod metal;
+pub mod dielectric;
+pub mod diffuse_light;
+pub mod isotropic;
+pub mod henyey_greenstein;
+pub mod conductor;
+pub mod rough_dielectric;
+pub mod principled;
\ No newline at end of file
@@ -0,0 +1,304 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use image::Rgb;
+
+use crate::ray::Ray;
+use crate::hittables::hittable::HitRecord;
+use crate::rgb_wrapper::RgbWrapper;
+use crate::vec3::Vec3;
+use crate::utils::{RenderRng, clamp, luminance};
+use crate::microfacet::{MIN_ALPHA, ggx_d, smith_g2, fresnel_dielectric};
+use crate::pdfs::{
+    pdf::Pdf,
+    cosine_pdf::CosinePdf,
+    ggx_pdf::GgxPdf,
+    rough_dielectric_pdf::{RoughDielectricPdf, transmission_half_vector},
+    mixture_pdf::MixturePdf};
+use crate::textures::{texture::Texture, solid_color::SolidColor};
+
+use super::material::{Scatter, ScatterRecord};
+
+// Disney style "principled" material: one set of artist friendly parameters
+// covering plastics, metals, glass, fabric and skin, close to glTF's metallic /
+// roughness model. It layers a diffuse base with sheen and a subsurface
+// approximation, a GGX specular lobe, a GGX clearcoat and rough transmission.
+//
+// Every parameter is a texture. The scalar ones use the average of the texture's
+// channels and are clamped to [0, 1].
+#[derive(Clone)]
+pub struct Principled {
+    pub base_color: Arc<dyn Texture + Send + Sync>,
+    pub metallic: Arc<dyn Texture + Send + Sync>,
+    pub roughness: Arc<dyn Texture + Send + Sync>,
+    // Strength of the dielectric specular reflection, 0.5 is an index of refraction of 1.5
+    pub specular: Arc<dyn Texture + Send + Sync>,
+    pub clearcoat: Arc<dyn Texture + Send + Sync>,
+    pub clearcoat_roughness: Arc<dyn Texture + Send + Sync>,
+    // Extra reflection at grazing angles, for cloth
+    pub sheen: Arc<dyn Texture + Send + Sync>,
+    pub transmission: Arc<dyn Texture + Send + Sync>,
+    // Blends the diffuse lobe towards a flatter look like light scattering under the surface
+    pub subsurface: Arc<dyn Texture + Send + Sync>,
+}
+
+// Parameter values looked up at a hit point
+struct Parameters {
+    base_color: RgbWrapper,
+    metallic: f64,
+    alpha: f64,
+    specular: f64,
+    clearcoat: f64,
+    clearcoat_alpha: f64,
+    sheen: f64,
+    transmission: f64,
+    subsurface: f64,
+}
+
+impl Parameters {
+    fn diffuse_weight(&self) -> f64 {
+        (1.0 - self.metallic) * (1.0 - self.transmission)
+    }
+
+    fn transmission_weight(&self) -> f64 {
+        (1.0 - self.metallic) * self.transmission
+    }
+
+    // Index of refraction matching the dielectric specular reflectance
+    fn ior(&self) -> f64 {
+        let sqrt_f0: f64 = (0.08 * self.specular).sqrt();
+        clamp((1.0 + sqrt_f0) / (1.0 - sqrt_f0), 1.0001, 3.0)
+    }
+
+    // Reflectance at normal incidence, tinted by the base color for metals
+    fn specular_f0(&self) -> RgbWrapper {
+        let dielectric: f64 = 0.08 * self.specular;
+        let base: [f64; 3] = self.base_color.0.0;
+        RgbWrapper(Rgb::from([0, 1, 2].map(|c| dielectric + (base[c] - dielectric) * self.metallic)))
+    }
+
+    // Whether the ray is inside a transmissive object. Opaque surfaces hit from
+    // behind, like a one-sided quad, are shaded the same as from the front.
+    fn inside(&self, rec: &HitRecord) -> bool {
+        !rec.front_face && self.transmission_weight() > 0.0
+    }
+
+    // Indices of refraction on the side the ray comes from and the other side
+    fn etas(&self, rec: &HitRecord) -> (f64, f64) {
+        if self.inside(rec) { (self.ior(), 1.0) } else { (1.0, self.ior()) }
+    }
+}
+
+impl Principled {
+    // A grey, half rough plastic, change the fields for anything else
+    pub fn new(base_color: Arc<dyn Texture + Send + Sync>) -> Self {
+        Self {
+            base_color,
+            metallic: constant(0.0),
+            roughness: constant(0.5),
+            specular: constant(0.5),
+            clearcoat: constant(0.0),
+            clearcoat_roughness: constant(0.1),
+            sheen: constant(0.0),
+            transmission: constant(0.0),
+            subsurface: constant(0.0)
+        }
+    }
+
+    fn parameters(&self, rec: &HitRecord) -> Parameters {
+        let scalar = |texture: &Arc<dyn Texture + Send + Sync>| {
+            let value: [f64; 3] = texture.value(rec.u, rec.v, &rec.p).0.0;
+            clamp((value[0] + value[1] + value[2]) / 3.0, 0.0, 1.0)
+        };
+        let roughness: f64 = scalar(&self.roughness);
+        let clearcoat_roughness: f64 = scalar(&self.clearcoat_roughness);
+
+        Parameters {
+            base_color: self.base_color.value(rec.u, rec.v, &rec.p),
+            metallic: scalar(&self.metallic),
+            alpha: (roughness*roughness).max(MIN_ALPHA),
+            specular: scalar(&self.specular),
+            clearcoat: scalar(&self.clearcoat),
+            clearcoat_alpha: (clearcoat_roughness*clearcoat_roughness).max(MIN_ALPHA),
+            sheen: scalar(&self.sheen),
+            transmission: scalar(&self.transmission),
+            subsurface: scalar(&self.subsurface)
+        }
+    }
+}
+
+impl Scatter for Principled {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, _rng: &mut RenderRng) -> Option<ScatterRecord> {
+        let params: Parameters = self.parameters(rec);
+        let wo: Vec3 = -r_in.direction().unit_vector();
+        let (eta_o, eta_i) = params.etas(rec);
+
+        // Inside a transmissive object only the dielectric boundary is left
+        if params.inside(rec) {
+            return Some(ScatterRecord::Sampled {
+                pdf: Box::new(RoughDielectricPdf::new(rec.normal, wo, params.alpha, eta_o, eta_i))
+            });
+        }
+
+        let components: Vec<(f64, Box<dyn Pdf>)> = vec![
+            (params.diffuse_weight(), Box::new(CosinePdf::new(rec.normal))),
+            (1.0, Box::new(GgxPdf::new(rec.normal, wo, params.alpha))),
+            (0.25 * params.clearcoat, Box::new(GgxPdf::new(rec.normal, wo, params.clearcoat_alpha))),
+            (params.transmission_weight(), Box::new(RoughDielectricPdf::new(rec.normal, wo, params.alpha, eta_o, eta_i)))
+        ];
+
+        Some(ScatterRecord::Sampled { pdf: Box::new(MixturePdf::new(components)) })
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> RgbWrapper {
+        let black: RgbWrapper = RgbWrapper(Rgb::from([0.0, 0.0, 0.0]));
+        let params: Parameters = self.parameters(rec);
+        let (eta_o, eta_i) = params.etas(rec);
+
+        let n: Vec3 = rec.normal;
+        let wo: Vec3 = -r_in.direction().unit_vector();
+        let wi: Vec3 = scattered.direction().unit_vector();
+        let cos_o: f64 = wo.dot(n);
+        let cos_i: f64 = wi.dot(n);
+        if cos_o <= 0.0 || cos_i == 0.0 {
+            return black;
+        }
+
+        if cos_i < 0.0 {
+            let Some((h, denom)) = transmission_half_vector(wo, wi, n, eta_o, eta_i) else {
+                return black;
+            };
+            let f: f64 = fresnel_dielectric(wo.dot(h), eta_o, eta_i);
+            let d: f64 = ggx_d(h.dot(n), params.alpha);
+            let g: f64 = smith_g2(cos_o, cos_i, params.alpha);
+            let btdf: f64 = (1.0 - f)*d*g * wi.dot(h).abs()*wo.dot(h) / (cos_o*denom*denom);
+
+            // Tinted when entering, the inside is plain glass
+            return if params.inside(rec) {
+                RgbWrapper(Rgb::from([btdf, btdf, btdf]))
+            } else {
+                params.base_color * (params.transmission_weight() * btdf)
+            };
+        }
+
+        let h: Vec3 = (wo + wi).unit_vector();
+        let cos_h: f64 = h.dot(n);
+        let cos_d: f64 = wi.dot(h);
+
+        if params.inside(rec) {
+            let f: f64 = fresnel_dielectric(wo.dot(h), eta_o, eta_i);
+            let value: f64 = f * ggx_d(cos_h, params.alpha) * smith_g2(cos_o, cos_i, params.alpha) / (4.0*cos_o);
+            return RgbWrapper(Rgb::from([value, value, value]));
+        }
+
+        // Diffuse with retro-reflection at grazing angles, blended with the
+        // subsurface approximation, and sheen on top
+        let fl: f64 = schlick_weight(cos_i);
+        let fv: f64 = schlick_weight(cos_o);
+        let fd90: f64 = 0.5 + 2.0*cos_d*cos_d*params.alpha.sqrt();
+        let fd: f64 = (1.0 + (fd90 - 1.0)*fl) * (1.0 + (fd90 - 1.0)*fv);
+        let fss90: f64 = cos_d*cos_d*params.alpha.sqrt();
+        let fss: f64 = (1.0 + (fss90 - 1.0)*fl) * (1.0 + (fss90 - 1.0)*fv);
+        let ss: f64 = 1.25 * (fss * (1.0/(cos_i + cos_o) - 0.5) + 0.5);
+        let diffuse: f64 = (fd + (ss - fd)*params.subsurface) / PI;
+
+        let sheen_color: RgbWrapper = RgbWrapper(Rgb::from(tint(params.base_color).map(|c| 0.5 + 0.5*c)));
+        let sheen: RgbWrapper = sheen_color * (params.sheen * schlick_weight(cos_d));
+
+        let base: RgbWrapper = (params.base_color * diffuse + sheen) * (params.diffuse_weight() * cos_i);
+
+        // D G F / (4 cos_o cos_i), times cos_i
+        let specular_f: RgbWrapper = schlick(params.specular_f0(), cos_d);
+        let specular: RgbWrapper = specular_f * (ggx_d(cos_h, params.alpha) * smith_g2(cos_o, cos_i, params.alpha) / (4.0*cos_o));
+
+        let clearcoat_f: f64 = 0.04 + 0.96*schlick_weight(cos_d);
+        let clearcoat: f64 = 0.25 * params.clearcoat * clearcoat_f
+            * ggx_d(cos_h, params.clearcoat_alpha) * smith_g2(cos_o, cos_i, params.clearcoat_alpha) / (4.0*cos_o);
+
+        base + specular + RgbWrapper(Rgb::from([clearcoat, clearcoat, clearcoat]))
+    }
+}
+
+fn constant(value: f64) -> Arc<dyn Texture + Send + Sync> {
+    Arc::new(SolidColor::new(Rgb::from([value, value, value])))
+}
+
+fn schlick_weight(cos_theta: f64) -> f64 {
+    (1.0 - clamp(cos_theta, 0.0, 1.0)).powi(5)
+}
+
+fn schlick(f0: RgbWrapper, cos_theta: f64) -> RgbWrapper {
+    let weight: f64 = schlick_weight(cos_theta);
+    RgbWrapper(Rgb::from(f0.0.0.map(|f| f + (1.0 - f)*weight)))
+}
+
+// Hue and saturation of a color at unit luminance
+fn tint(color: RgbWrapper) -> [f64; 3] {
+    let luminance: f64 = luminance(color.0.0);
+    if luminance > 0.0 { color.0.0.map(|c| c / luminance) } else { [1.0, 1.0, 1.0] }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use image::Rgb;
+
+    use crate::{
+        hittables::{hittable::{Hittable, HitRecord}, quad::Quad},
+        materials::material::{Material, Scatter, ScatterRecord},
+        ray::Ray,
+        textures::solid_color::SolidColor,
+        utils::new_rng,
+        vec3::{Point3, Vec3}};
+
+    use super::{Principled, constant};
+
+    // A red, rough and opaque quad in the z = 0 plane facing +Z
+    fn opaque_quad() -> Quad {
+        let mut principled: Principled = Principled::new(Arc::new(SolidColor::new(Rgb::from([0.8, 0.1, 0.1]))));
+        principled.roughness = constant(0.8);
+        let material: Material = Material::new(Arc::new(principled));
+        Quad::new(Point3::new(-2.0, -2.0, 0.0), Vec3::new(4.0, 0.0, 0.0), Vec3::new(0.0, 4.0, 0.0), material)
+    }
+
+    fn hit(quad: &Quad, ray: &Ray) -> HitRecord {
+        let mut rec: HitRecord = HitRecord::new_empty();
+        assert!(quad.hit(ray, 0.0001, f64::INFINITY, &mut rec));
+        rec
+    }
+
+    #[test]
+    fn opaque_back_face_shades_like_front_face() {
+        let quad: Quad = opaque_quad();
+        let front_ray: Ray = Ray::new(Point3::new(0.1, 0.2, 1.0), Vec3::new(0.0, 0.0, -1.0), 0.0);
+        let back_ray: Ray = Ray::new(Point3::new(0.1, 0.2, -1.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        let front: HitRecord = hit(&quad, &front_ray);
+        let back: HitRecord = hit(&quad, &back_ray);
+        assert!(front.front_face && !back.front_face);
+
+        // The same directions mirrored through the quad's plane
+        for direction in [Vec3::new(0.3, 0.2, 0.9), Vec3::new(-0.5, 0.1, 0.4), Vec3::new(0.0, 0.0, 1.0)] {
+            let mirrored: Vec3 = Vec3::new(direction.x(), direction.y(), -direction.z());
+            let front_value: Rgb<f64> = quad.material.eval(&front_ray, &front, &Ray::new(front.p, direction, 0.0)).0;
+            let back_value: Rgb<f64> = quad.material.eval(&back_ray, &back, &Ray::new(back.p, mirrored, 0.0)).0;
+            assert!(front_value.0[0] > 0.0);
+            for channel in 0..3 {
+                assert!((front_value.0[channel] - back_value.0[channel]).abs() < 1e-12, "{:?} != {:?}", front_value, back_value);
+            }
+        }
+    }
+
+    #[test]
+    fn opaque_back_face_lets_no_light_through() {
+        let quad: Quad = opaque_quad();
+        let back_ray: Ray = Ray::new(Point3::new(0.1, 0.2, -1.0), Vec3::new(0.0, 0.0, 1.0), 0.0);
+        let back: HitRecord = hit(&quad, &back_ray);
+        let through: Ray = Ray::new(back.p, Vec3::new(0.0, 0.0, 1.0), 0.0);
+
+        assert_eq!(quad.material.eval(&back_ray, &back, &through).0, Rgb::from([0.0, 0.0, 0.0]));
+        match quad.material.scatter(&back_ray, &back, &mut new_rng(0, 0)) {
+            Some(ScatterRecord::Sampled { pdf }) => assert_eq!(pdf.value(&through.direction()), 0.0),
+            _ => panic!("expected a sampled scatter"),
+        }
+    }
+}
\ No newline at end of file
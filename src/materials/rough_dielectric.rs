@@ -0,0 +1,167 @@
+use image::Rgb;
+
+use crate::ray::Ray;
+use crate::hittables::hittable::HitRecord;
+use crate::rgb_wrapper::RgbWrapper;
+use crate::utils::{RenderRng, random_double};
+use crate::vec3::{Vec3, reflect, refract};
+use crate::microfacet::{MIN_ALPHA, ggx_d, smith_g2, fresnel_dielectric};
+use crate::pdfs::rough_dielectric_pdf::{RoughDielectricPdf, transmission_half_vector};
+
+use super::material::{Scatter, ScatterRecord};
+
+// Frosted glass: a dielectric boundary with a GGX microfacet distribution that
+// both reflects and transmits (Walter et al. 2007). A roughness of zero gives
+// clear glass with exact Fresnel reflectance.
+#[derive(Clone, Copy)]
+pub struct RoughDielectric {
+    pub ir: f64,
+    // Perceptual roughness between 0 and 1, squared to get the GGX alpha
+    pub roughness: f64,
+}
+
+impl RoughDielectric {
+    pub fn new(index_of_refraction: f64, roughness: f64) -> Self {
+        Self {
+            ir: index_of_refraction,
+            roughness
+        }
+    }
+
+    fn alpha(&self) -> f64 {
+        self.roughness*self.roughness
+    }
+
+    // Indices of refraction on the side the ray comes from and the other side
+    fn etas(&self, rec: &HitRecord) -> (f64, f64) {
+        if rec.front_face { (1.0, self.ir) } else { (self.ir, 1.0) }
+    }
+}
+
+impl Scatter for RoughDielectric {
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, rng: &mut RenderRng) -> Option<ScatterRecord> {
+        let (eta_o, eta_i) = self.etas(rec);
+        let unit_direction: Vec3 = r_in.direction().unit_vector();
+
+        if self.alpha() < MIN_ALPHA {
+            let cos_theta: f64 = (-unit_direction).dot(rec.normal).min(1.0);
+            let direction: Vec3 = if random_double(rng) < fresnel_dielectric(cos_theta, eta_o, eta_i) {
+                reflect(&unit_direction, &rec.normal)
+            } else {
+                refract(&unit_direction, &rec.normal, eta_o / eta_i)
+            };
+            return Some(ScatterRecord::Specular {
+                attenuation: RgbWrapper(Rgb::from([1.0, 1.0, 1.0])),
+                ray: Ray::new(rec.p, direction, r_in.time())
+            });
+        }
+
+        Some(ScatterRecord::Sampled {
+            pdf: Box::new(RoughDielectricPdf::new(rec.normal, -unit_direction, self.alpha(), eta_o, eta_i))
+        })
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, scattered: &Ray) -> RgbWrapper {
+        let black: RgbWrapper = RgbWrapper(Rgb::from([0.0, 0.0, 0.0]));
+        if self.alpha() < MIN_ALPHA {
+            return black;
+        }
+
+        let (eta_o, eta_i) = self.etas(rec);
+        let n: Vec3 = rec.normal;
+        let wo: Vec3 = -r_in.direction().unit_vector();
+        let wi: Vec3 = scattered.direction().unit_vector();
+        let cos_o: f64 = wo.dot(n);
+        let cos_i: f64 = wi.dot(n);
+        if cos_o <= 0.0 || cos_i == 0.0 {
+            return black;
+        }
+
+        let value: f64 = if cos_i > 0.0 {
+            let h: Vec3 = (wo + wi).unit_vector();
+            let f: f64 = fresnel_dielectric(wo.dot(h), eta_o, eta_i);
+            let d: f64 = ggx_d(h.dot(n), self.alpha());
+            let g: f64 = smith_g2(cos_o, cos_i, self.alpha());
+            f*d*g / (4.0*cos_o)
+        } else {
+            let Some((h, denom)) = transmission_half_vector(wo, wi, n, eta_o, eta_i) else {
+                return black;
+            };
+            let f: f64 = fresnel_dielectric(wo.dot(h), eta_o, eta_i);
+            let d: f64 = ggx_d(h.dot(n), self.alpha());
+            let g: f64 = smith_g2(cos_o, cos_i, self.alpha());
+            // Radiance is carried across the boundary, so the eta squared of the
+            // BTDF cancels with the change in radiance
+            (1.0 - f)*d*g * wi.dot(h).abs()*wo.dot(h) / (cos_o*denom*denom)
+        };
+
+        RgbWrapper(Rgb::from([value, value, value]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        hittables::hittable::HitRecord,
+        materials::material::Scatter,
+        pdfs::{pdf::Pdf, rough_dielectric_pdf::RoughDielectricPdf},
+        ray::Ray,
+        utils::new_rng,
+        vec3::Vec3};
+
+    use super::RoughDielectric;
+
+    // Points u along each side of the unit square
+    const SAMPLES: usize = 256;
+
+    // Fraction of the light coming from all directions, on both sides of the
+    // boundary, that ends up going towards wo. Radiance inside a furnace is eta
+    // squared times as bright in the glass as outside it, so that's how bright
+    // the light refracted towards wo is
+    fn albedo(glass: &RoughDielectric, wo: Vec3, front_face: bool) -> f64 {
+        let mut rec: HitRecord = HitRecord::new_empty();
+        rec.normal = Vec3::new(0.0, 0.0, 1.0);
+        rec.front_face = front_face;
+        let (eta_o, eta_i) = glass.etas(&rec);
+        let r_in: Ray = Ray::new(rec.p + wo, -wo, 0.0);
+        let pdf: RoughDielectricPdf = RoughDielectricPdf::new(rec.normal, wo, glass.alpha(), eta_o, eta_i);
+
+        let mut rng = new_rng(0, 0);
+        let mut sum: f64 = 0.0;
+        for i in 0..SAMPLES {
+            for j in 0..SAMPLES {
+                let u: [f64; 2] = [(i as f64 + 0.5) / SAMPLES as f64, (j as f64 + 0.5) / SAMPLES as f64];
+                let wi: Vec3 = pdf.generate(u, &mut rng);
+                let density: f64 = pdf.value(&wi);
+                if density > 0.0 {
+                    let radiance: f64 = if wi.dot(rec.normal) < 0.0 { (eta_i / eta_o).powi(2) } else { 1.0 };
+                    sum += glass.eval(&r_in, &rec, &Ray::new(rec.p, wi, 0.0)).0.0[0] * radiance / density;
+                }
+            }
+        }
+        sum / (SAMPLES*SAMPLES) as f64
+    }
+
+    #[test]
+    fn white_furnace_lets_through_no_more_than_comes_in() {
+        // Into the glass and out of it, reflected and refracted light together
+        let glass: RoughDielectric = RoughDielectric::new(1.5, 1.0);
+        for front_face in [true, false] {
+            for wo in [Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.5, 0.2, 0.8), Vec3::new(1.0, 0.0, 0.2)] {
+                let albedo: f64 = albedo(&glass, wo.unit_vector(), front_face);
+                assert!(albedo <= 1.0, "{}", albedo);
+            }
+        }
+    }
+
+    #[test]
+    fn white_furnace_loses_little_on_smooth_surfaces() {
+        let glass: RoughDielectric = RoughDielectric::new(1.5, 0.3);
+        for front_face in [true, false] {
+            for wo in [Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.5, 0.2, 0.8)] {
+                let albedo: f64 = albedo(&glass, wo.unit_vector(), front_face);
+                assert!(albedo <= 1.0 && albedo > 0.95, "{}", albedo);
+            }
+        }
+    }
+}
\ No newline at end of file
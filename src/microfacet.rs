@@ -0,0 +1,86 @@
+use std::f64::consts::PI;
+
+use crate::vec3::Vec3;
+
+// Building blocks for microfacet materials: the GGX (Trowbridge-Reitz)
+// distribution of microfacet normals, Smith shadowing-masking and Fresnel
+// reflectance. Angles are given as cosines to the relevant normal.
+
+// Roughness values below this are treated as perfectly smooth
+pub const MIN_ALPHA: f64 = 1e-3;
+
+// GGX normal distribution function for a microfacet normal at angle theta_h
+// from the macro surface normal
+pub fn ggx_d(cos_theta_h: f64, alpha: f64) -> f64 {
+    if cos_theta_h <= 0.0 {
+        return 0.0;
+    }
+
+    let alpha2: f64 = alpha*alpha;
+    let cos2: f64 = cos_theta_h*cos_theta_h;
+    let denom: f64 = cos2*(alpha2 - 1.0) + 1.0;
+    alpha2 / (PI*denom*denom)
+}
+
+// Microfacet normal around +Z distributed as D(h) * cos(theta_h), from a point u
+// in the unit square
+pub fn sample_ggx_normal(u: [f64; 2], alpha: f64) -> Vec3 {
+    let phi: f64 = 2.0*PI*u[0];
+    let cos_theta: f64 = ((1.0 - u[1]) / (1.0 + (alpha*alpha - 1.0)*u[1])).sqrt();
+    let sin_theta: f64 = (1.0 - cos_theta*cos_theta).max(0.0).sqrt();
+
+    Vec3::new(sin_theta*phi.cos(), sin_theta*phi.sin(), cos_theta)
+}
+
+fn smith_lambda(cos_theta: f64, alpha: f64) -> f64 {
+    let cos2: f64 = cos_theta*cos_theta;
+    if cos2 == 0.0 {
+        return f64::INFINITY;
+    }
+    let tan2: f64 = (1.0 - cos2) / cos2;
+    ((1.0 + alpha*alpha*tan2).sqrt() - 1.0) / 2.0
+}
+
+// Height-correlated Smith shadowing-masking for the two directions
+pub fn smith_g2(cos_theta_o: f64, cos_theta_i: f64, alpha: f64) -> f64 {
+    1.0 / (1.0 + smith_lambda(cos_theta_o.abs(), alpha) + smith_lambda(cos_theta_i.abs(), alpha))
+}
+
+// Fraction of unpolarized light reflected at the boundary between two
+// dielectrics, going from index eta_i into index eta_t
+pub fn fresnel_dielectric(cos_theta_i: f64, eta_i: f64, eta_t: f64) -> f64 {
+    let cos_i: f64 = cos_theta_i.abs().min(1.0);
+    let sin_t: f64 = eta_i / eta_t * (1.0 - cos_i*cos_i).max(0.0).sqrt();
+    if sin_t >= 1.0 {
+        // Total internal reflection
+        return 1.0;
+    }
+    let cos_t: f64 = (1.0 - sin_t*sin_t).max(0.0).sqrt();
+
+    let r_parallel: f64 = (eta_t*cos_i - eta_i*cos_t) / (eta_t*cos_i + eta_i*cos_t);
+    let r_perpendicular: f64 = (eta_i*cos_i - eta_t*cos_t) / (eta_i*cos_i + eta_t*cos_t);
+    (r_parallel*r_parallel + r_perpendicular*r_perpendicular) / 2.0
+}
+
+// Fraction of unpolarized light reflected by a conductor with the complex index
+// of refraction eta + i k, from air
+pub fn fresnel_conductor(cos_theta_i: f64, eta: f64, k: f64) -> f64 {
+    let cos_i: f64 = cos_theta_i.abs().min(1.0);
+    let cos2: f64 = cos_i*cos_i;
+    let sin2: f64 = 1.0 - cos2;
+    let eta2: f64 = eta*eta;
+    let k2: f64 = k*k;
+
+    let t0: f64 = eta2 - k2 - sin2;
+    let a2_plus_b2: f64 = (t0*t0 + 4.0*eta2*k2).sqrt();
+    let t1: f64 = a2_plus_b2 + cos2;
+    let a: f64 = (0.5*(a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t2: f64 = 2.0*cos_i*a;
+    let r_s: f64 = (t1 - t2) / (t1 + t2);
+
+    let t3: f64 = cos2*a2_plus_b2 + sin2*sin2;
+    let t4: f64 = t2*sin2;
+    let r_p: f64 = r_s*(t3 - t4) / (t3 + t4);
+
+    (r_p + r_s) / 2.0
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+use crate::vec3::{Vec3, F64Multiplier};
+
+// Orthonormal basis, used to turn directions sampled around the Z axis into
+// directions around an arbitrary axis w
+#[derive(Clone, Copy, Debug)]
+pub struct Onb {
+    axis: [Vec3; 3],
+}
+
+impl Onb {
+    pub fn build_from_w(w: Vec3) -> Self {
+        let unit_w: Vec3 = w.unit_vector();
+        let a: Vec3 = if unit_w.x().abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let v: Vec3 = unit_w.cross(a).unit_vector();
+        let u: Vec3 = unit_w.cross(v);
+
+        Self {
+            axis: [u, v, unit_w]
+        }
+    }
+
+    pub fn w(&self) -> Vec3 {
+        self.axis[2]
+    }
+
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        F64Multiplier(a.x())*self.axis[0] + F64Multiplier(a.y())*self.axis[1] + F64Multiplier(a.z())*self.axis[2]
+    }
+}
\ No newline at end of file
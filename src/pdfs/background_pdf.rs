@@ -0,0 +1,28 @@
+use crate::vec3::Vec3;
+use crate::backgrounds::background::Background;
+use crate::utils::RenderRng;
+
+use super::pdf::Pdf;
+
+// Directions towards the bright parts of an importance sampled background
+pub struct BackgroundPdf<'a> {
+    background: &'a dyn Background,
+}
+
+impl<'a> BackgroundPdf<'a> {
+    pub fn new(background: &'a dyn Background) -> Self {
+        Self {
+            background
+        }
+    }
+}
+
+impl Pdf for BackgroundPdf<'_> {
+    fn value(&self, direction: &Vec3) -> f64 {
+        self.background.pdf_value(direction)
+    }
+
+    fn generate(&self, u: [f64; 2], _rng: &mut RenderRng) -> Vec3 {
+        self.background.random(u)
+    }
+}
\ No newline at end of file
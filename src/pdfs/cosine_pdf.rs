@@ -0,0 +1,31 @@
+use std::f64::consts::PI;
+
+use crate::vec3::{Vec3, sample_cosine_direction};
+use crate::onb::Onb;
+use crate::utils::RenderRng;
+
+use super::pdf::Pdf;
+
+// Cosine weighted directions over the hemisphere around w, matching a Lambertian surface
+pub struct CosinePdf {
+    uvw: Onb,
+}
+
+impl CosinePdf {
+    pub fn new(w: Vec3) -> Self {
+        Self {
+            uvw: Onb::build_from_w(w)
+        }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: &Vec3) -> f64 {
+        let cosine: f64 = direction.unit_vector().dot(self.uvw.w());
+        if cosine <= 0.0 { 0.0 } else { cosine / PI }
+    }
+
+    fn generate(&self, u: [f64; 2], _rng: &mut RenderRng) -> Vec3 {
+        self.uvw.local(sample_cosine_direction(u))
+    }
+}
\ No newline at end of file
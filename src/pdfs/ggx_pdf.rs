@@ -0,0 +1,96 @@
+use crate::vec3::{Vec3, reflect};
+use crate::onb::Onb;
+use crate::microfacet::{ggx_d, sample_ggx_normal};
+use crate::utils::RenderRng;
+
+use super::pdf::Pdf;
+
+// Reflections off a rough surface with a GGX (Trowbridge-Reitz) distribution of
+// microfacet normals. Microfacet normals are sampled in proportion to
+// D(h) * cos(theta_h) and the outgoing direction mirrored about them.
+pub struct GgxPdf {
+    uvw: Onb,
+    // Unit direction towards where the light is going, away from the surface
+    wo: Vec3,
+    alpha: f64,
+}
+
+impl GgxPdf {
+    // alpha is the roughness of the distribution, usually the square of the
+    // perceptual roughness artists work with
+    pub fn new(normal: Vec3, wo: Vec3, alpha: f64) -> Self {
+        Self {
+            uvw: Onb::build_from_w(normal),
+            wo: wo.unit_vector(),
+            alpha
+        }
+    }
+}
+
+impl Pdf for GgxPdf {
+    fn value(&self, direction: &Vec3) -> f64 {
+        let wi: Vec3 = direction.unit_vector();
+        if wi.dot(self.uvw.w()) <= 0.0 {
+            return 0.0;
+        }
+
+        let h: Vec3 = (self.wo + wi).unit_vector();
+        let cos_theta_h: f64 = h.dot(self.uvw.w());
+        let wo_dot_h: f64 = self.wo.dot(h).abs();
+        if wo_dot_h == 0.0 {
+            return 0.0;
+        }
+
+        // Change of variables from the half vector to the reflected direction
+        ggx_d(cos_theta_h, self.alpha) * cos_theta_h / (4.0*wo_dot_h)
+    }
+
+    fn generate(&self, u: [f64; 2], _rng: &mut RenderRng) -> Vec3 {
+        let h: Vec3 = self.uvw.local(sample_ggx_normal(u, self.alpha));
+        reflect(&(-self.wo), &h)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{pdfs::pdf::tests::{integrate, fraction_generated, assert_generate_matches_value}, utils::new_rng, vec3::Vec3};
+
+    use super::GgxPdf;
+
+    fn pdfs() -> Vec<GgxPdf> {
+        let normal: Vec3 = Vec3::new(0.0, 0.0, 1.0);
+        let mut pdfs: Vec<GgxPdf> = Vec::new();
+        for alpha in [0.2, 0.5, 1.0] {
+            for wo in [Vec3::new(0.0, 0.0, 1.0), Vec3::new(1.0, 0.5, 1.0), Vec3::new(1.0, 0.0, 0.2)] {
+                pdfs.push(GgxPdf::new(normal, wo, alpha));
+            }
+        }
+        pdfs
+    }
+
+    #[test]
+    fn value_integrates_to_one_for_smooth_surfaces() {
+        // Almost no reflections off the microfacets end up below the surface
+        let pdf: GgxPdf = GgxPdf::new(Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.2, 1.0), 0.1);
+        let integral: f64 = integrate(&pdf);
+        assert!((integral - 1.0).abs() < 0.02, "{}", integral);
+    }
+
+    #[test]
+    fn value_integrates_to_the_directions_above_the_surface() {
+        // Rougher surfaces reflect some directions below the surface, which
+        // value gives no density
+        for pdf in pdfs() {
+            let integral: f64 = integrate(&pdf);
+            let generated: f64 = fraction_generated(&pdf, &mut new_rng(0, 0));
+            assert!(integral <= 1.0 + 1e-3 && (integral - generated).abs() < 0.005, "value integrates to {}, {} generated", integral, generated);
+        }
+    }
+
+    #[test]
+    fn generate_matches_value() {
+        for pdf in pdfs() {
+            assert_generate_matches_value(&pdf, &mut new_rng(0, 0), 0.005);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,55 @@
+use std::f64::consts::PI;
+
+use crate::vec3::Vec3;
+use crate::onb::Onb;
+use crate::utils::{RenderRng, clamp};
+
+use super::pdf::Pdf;
+
+// Henyey-Greenstein phase function around the direction a ray travels in, which
+// it samples exactly. g must be strictly between -1 and 1.
+pub struct HenyeyGreensteinPdf {
+    uvw: Onb,
+    g: f64,
+}
+
+impl HenyeyGreensteinPdf {
+    pub fn new(direction: Vec3, g: f64) -> Self {
+        Self {
+            uvw: Onb::build_from_w(direction),
+            g
+        }
+    }
+
+    // Cosine of the angle between the incoming and scattered directions, sampled
+    // by inverting the phase function's CDF at xi
+    fn sample_cos_theta(&self, xi: f64) -> f64 {
+        if self.g.abs() < 1e-3 {
+            return 1.0 - 2.0*xi;
+        }
+
+        let g: f64 = self.g;
+        let square_term: f64 = (1.0 - g*g) / (1.0 - g + 2.0*g*xi);
+        clamp((1.0 + g*g - square_term*square_term) / (2.0*g), -1.0, 1.0)
+    }
+}
+
+impl Pdf for HenyeyGreensteinPdf {
+    fn value(&self, direction: &Vec3) -> f64 {
+        phase(direction.unit_vector().dot(self.uvw.w()), self.g)
+    }
+
+    fn generate(&self, u: [f64; 2], _rng: &mut RenderRng) -> Vec3 {
+        let cos_theta: f64 = self.sample_cos_theta(u[0]);
+        let sin_theta: f64 = (1.0 - cos_theta*cos_theta).max(0.0).sqrt();
+        let phi: f64 = 2.0*PI*u[1];
+
+        self.uvw.local(Vec3::new(sin_theta*phi.cos(), sin_theta*phi.sin(), cos_theta))
+    }
+}
+
+// The phase function for the cosine of the angle between the incoming and scattered directions
+pub fn phase(cos_theta: f64, g: f64) -> f64 {
+    let denom: f64 = 1.0 + g*g - 2.0*g*cos_theta;
+    (1.0 - g*g) / (4.0*PI*denom*denom.sqrt())
+}
\ No newline at end of file
@@ -0,0 +1,30 @@
+use crate::vec3::{Point3, Vec3};
+use crate::hittables::hittable::Hittable;
+use crate::utils::RenderRng;
+
+use super::pdf::Pdf;
+
+// Directions from origin towards random points on an object, usually the lights
+pub struct HittablePdf<'a> {
+    object: &'a dyn Hittable,
+    origin: Point3,
+}
+
+impl<'a> HittablePdf<'a> {
+    pub fn new(object: &'a dyn Hittable, origin: Point3) -> Self {
+        Self {
+            object,
+            origin
+        }
+    }
+}
+
+impl Pdf for HittablePdf<'_> {
+    fn value(&self, direction: &Vec3) -> f64 {
+        self.object.pdf_value(&self.origin, direction)
+    }
+
+    fn generate(&self, u: [f64; 2], _rng: &mut RenderRng) -> Vec3 {
+        self.object.random(&self.origin, u)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,93 @@
+use crate::vec3::Vec3;
+use crate::utils::RenderRng;
+use crate::samplers::hash::ONE_MINUS_EPSILON;
+
+use super::pdf::Pdf;
+
+// Picks one of several pdfs at random in proportion to their weights, e.g. to
+// sample the lobes of a layered material or mix light and material sampling
+pub struct MixturePdf<'a> {
+    components: Vec<(f64, Box<dyn Pdf + 'a>)>,
+}
+
+impl<'a> MixturePdf<'a> {
+    // Components with a weight of zero are dropped, the rest are normalized
+    pub fn new(components: Vec<(f64, Box<dyn Pdf + 'a>)>) -> Self {
+        let mut components: Vec<(f64, Box<dyn Pdf + 'a>)> = components.into_iter().filter(|(weight, _)| *weight > 0.0).collect();
+        let total: f64 = components.iter().map(|(weight, _)| weight).sum();
+        for (weight, _) in components.iter_mut() {
+            *weight /= total;
+        }
+
+        Self {
+            components
+        }
+    }
+}
+
+impl Pdf for MixturePdf<'_> {
+    fn value(&self, direction: &Vec3) -> f64 {
+        self.components.iter().map(|(weight, pdf)| weight * pdf.value(direction)).sum()
+    }
+
+    // The first dimension of u picks the component and is stretched back over
+    // [0, 1) for it, so evenly spread points stay evenly spread within each component
+    fn generate(&self, u: [f64; 2], rng: &mut RenderRng) -> Vec3 {
+        let mut choice: f64 = u[0];
+        for (weight, pdf) in &self.components {
+            if choice < *weight {
+                return pdf.generate([(choice / weight).min(ONE_MINUS_EPSILON), u[1]], rng);
+            }
+            choice -= weight;
+        }
+
+        match self.components.last() {
+            Some((_, pdf)) => pdf.generate([ONE_MINUS_EPSILON, u[1]], rng),
+            None => Vec3::new_empty(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{pdfs::pdf::Pdf, samplers::hash::ONE_MINUS_EPSILON, utils::{RenderRng, new_rng}, vec3::Vec3};
+
+    use super::MixturePdf;
+
+    // Returns the u it was given, with the number it was made with as z
+    struct EchoPdf(f64);
+
+    impl Pdf for EchoPdf {
+        fn value(&self, _direction: &Vec3) -> f64 {
+            0.0
+        }
+
+        fn generate(&self, u: [f64; 2], _rng: &mut RenderRng) -> Vec3 {
+            Vec3::new(u[0], u[1], self.0)
+        }
+    }
+
+    #[test]
+    fn remapped_dimension_stays_in_the_unit_interval() {
+        let pdf: MixturePdf = MixturePdf::new(vec![
+            (0.1, Box::new(EchoPdf(0.0))),
+            (0.0, Box::new(EchoPdf(1.0))),
+            (0.2, Box::new(EchoPdf(2.0))),
+            (0.7, Box::new(EchoPdf(3.0)))]);
+        let mut rng: RenderRng = new_rng(0, 0);
+
+        let mut previous: Vec3 = Vec3::new(0.0, 0.0, 0.0);
+        for i in 0..=1000 {
+            let u: f64 = (i as f64 / 1000.0).min(ONE_MINUS_EPSILON);
+            let direction: Vec3 = pdf.generate([u, 0.25], &mut rng);
+            assert!((0.0..1.0).contains(&direction.x()), "u {}: {}", u, direction.x());
+            assert_eq!(direction.y(), 0.25);
+            assert_ne!(direction.z(), 1.0, "a component with no weight was picked");
+            // Within a component the remapped value grows with u
+            if direction.z() == previous.z() {
+                assert!(direction.x() >= previous.x());
+            }
+            previous = direction;
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,9 @@
+pub mod pdf;
+pub mod cosine_pdf;
+pub mod sphere_pdf;
+pub mod henyey_greenstein_pdf;
+pub mod ggx_pdf;
+pub mod hittable_pdf;
+pub mod mixture_pdf;
+pub mod rough_dielectric_pdf;
+pub mod background_pdf;
\ No newline at end of file
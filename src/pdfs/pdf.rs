@@ -0,0 +1,93 @@
+use crate::vec3::Vec3;
+use crate::utils::RenderRng;
+
+// Probability density over directions, used to importance sample where rays go
+pub trait Pdf {
+    // Density per unit solid angle of generate() returning the given direction
+    fn value(&self, direction: &Vec3) -> f64;
+    // Direction picked by u, a point in the unit square. Choices that don't fit in
+    // the two dimensions of u, like reflecting or refracting, use rng.
+    fn generate(&self, u: [f64; 2], rng: &mut RenderRng) -> Vec3;
+}
+
+// Checks shared by the tests of the pdfs
+#[cfg(test)]
+pub mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{utils::RenderRng, vec3::Vec3};
+
+    use super::Pdf;
+
+    // Bins the sphere is split into along z and along phi
+    const BINS: usize = 16;
+    // Cells each bin is split into along z and along phi to integrate over it
+    const CELLS_PER_BIN: usize = 16;
+    // Points u along each side of the unit square
+    const SAMPLES: usize = 256;
+
+    // Middle of cell (i, j) of a grid of equal area cells, even in z and phi
+    fn cell_direction(i: usize, j: usize, cells: usize) -> Vec3 {
+        let z: f64 = -1.0 + 2.0*(i as f64 + 0.5) / cells as f64;
+        let phi: f64 = -PI + 2.0*PI*(j as f64 + 0.5) / cells as f64;
+        let r: f64 = (1.0 - z*z).max(0.0).sqrt();
+        Vec3::new(r*phi.cos(), r*phi.sin(), z)
+    }
+
+    fn bin(direction: Vec3) -> usize {
+        let d: Vec3 = direction.unit_vector();
+        let i: usize = (((d.z() + 1.0) / 2.0 * BINS as f64) as usize).min(BINS - 1);
+        let j: usize = (((d.y().atan2(d.x()) + PI) / (2.0*PI) * BINS as f64) as usize).min(BINS - 1);
+        i*BINS + j
+    }
+
+    // Integral of value over the sphere, by the midpoint rule
+    pub fn integrate(pdf: &dyn Pdf) -> f64 {
+        let cells: usize = BINS*CELLS_PER_BIN;
+        let area: f64 = 4.0*PI / (cells*cells) as f64;
+        (0..cells).flat_map(|i| (0..cells).map(move |j| (i, j)))
+            .map(|(i, j)| pdf.value(&cell_direction(i, j, cells)) * area)
+            .sum()
+    }
+
+    // Fraction of the directions generated from an even grid of points u that
+    // the renderer can use, that is ones that aren't zero and that value gives a density
+    pub fn fraction_generated(pdf: &dyn Pdf, rng: &mut RenderRng) -> f64 {
+        let mut generated: usize = 0;
+        for_each_generated(pdf, rng, |_| generated += 1);
+        generated as f64 / (SAMPLES*SAMPLES) as f64
+    }
+
+    // Splits the sphere into regions and checks that as many of the generated
+    // directions land in each as value integrates to over it
+    pub fn assert_generate_matches_value(pdf: &dyn Pdf, rng: &mut RenderRng, tolerance: f64) {
+        let cells: usize = BINS*CELLS_PER_BIN;
+        let area: f64 = 4.0*PI / (cells*cells) as f64;
+        let mut expected: Vec<f64> = vec![0.0; BINS*BINS];
+        for i in 0..cells {
+            for j in 0..cells {
+                expected[(i / CELLS_PER_BIN)*BINS + j / CELLS_PER_BIN] += pdf.value(&cell_direction(i, j, cells)) * area;
+            }
+        }
+
+        let mut generated: Vec<f64> = vec![0.0; BINS*BINS];
+        for_each_generated(pdf, rng, |direction| generated[bin(direction)] += 1.0 / (SAMPLES*SAMPLES) as f64);
+
+        for (index, (generated, expected)) in generated.iter().zip(&expected).enumerate() {
+            assert!((generated - expected).abs() < tolerance,
+                "bin {}: {} of the directions were generated there but value integrates to {}", index, generated, expected);
+        }
+    }
+
+    fn for_each_generated(pdf: &dyn Pdf, rng: &mut RenderRng, mut f: impl FnMut(Vec3)) {
+        for i in 0..SAMPLES {
+            for j in 0..SAMPLES {
+                let u: [f64; 2] = [(i as f64 + 0.5) / SAMPLES as f64, (j as f64 + 0.5) / SAMPLES as f64];
+                let direction: Vec3 = pdf.generate(u, rng);
+                if !direction.near_zero() && pdf.value(&direction) > 0.0 {
+                    f(direction);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
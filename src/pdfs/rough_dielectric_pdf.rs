@@ -0,0 +1,163 @@
+use crate::vec3::{Vec3, F64Multiplier, reflect};
+use crate::onb::Onb;
+use crate::utils::{RenderRng, random_double};
+use crate::microfacet::{ggx_d, sample_ggx_normal, fresnel_dielectric};
+
+use super::pdf::Pdf;
+
+// Reflection and transmission through a rough dielectric boundary (Walter et al.
+// 2007). A microfacet normal is sampled from the GGX distribution, then the
+// direction is either mirrored about it or refracted through it, in proportion
+// to the Fresnel reflectance.
+pub struct RoughDielectricPdf {
+    uvw: Onb,
+    // Unit direction towards where the light is going, on the normal's side
+    wo: Vec3,
+    alpha: f64,
+    // Index of refraction on the normal's side and on the other side
+    eta_o: f64,
+    eta_i: f64,
+}
+
+impl RoughDielectricPdf {
+    pub fn new(normal: Vec3, wo: Vec3, alpha: f64, eta_o: f64, eta_i: f64) -> Self {
+        Self {
+            uvw: Onb::build_from_w(normal),
+            wo: wo.unit_vector(),
+            alpha,
+            eta_o,
+            eta_i
+        }
+    }
+}
+
+impl Pdf for RoughDielectricPdf {
+    // A reflection can end up below the surface and a refraction above it, so
+    // both ways of reaching the direction count
+    fn value(&self, direction: &Vec3) -> f64 {
+        if direction.near_zero() {
+            return 0.0;
+        }
+        let n: Vec3 = self.uvw.w();
+        let wi: Vec3 = direction.unit_vector();
+
+        let mut pdf: f64 = 0.0;
+
+        let h: Vec3 = (self.wo + wi).unit_vector();
+        let wo_dot_h: f64 = self.wo.dot(h);
+        if wo_dot_h > 0.0 {
+            let f: f64 = fresnel_dielectric(wo_dot_h, self.eta_o, self.eta_i);
+            pdf += f * ggx_d(h.dot(n), self.alpha) * h.dot(n) / (4.0*wo_dot_h);
+        }
+
+        if let Some((h, denom)) = transmission_half_vector(self.wo, wi, n, self.eta_o, self.eta_i) {
+            let eta: f64 = self.eta_i / self.eta_o;
+            let f: f64 = fresnel_dielectric(self.wo.dot(h), self.eta_o, self.eta_i);
+            // Change of variables from the half vector to the refracted direction
+            pdf += (1.0 - f) * ggx_d(h.dot(n), self.alpha) * h.dot(n) * eta*eta*wi.dot(h).abs() / (denom*denom);
+        }
+
+        pdf
+    }
+
+    // Facets facing away from wo can't be hit, the zero vector is returned for them
+    fn generate(&self, u: [f64; 2], rng: &mut RenderRng) -> Vec3 {
+        let h: Vec3 = self.uvw.local(sample_ggx_normal(u, self.alpha));
+        let cos_o: f64 = self.wo.dot(h);
+        if cos_o <= 0.0 {
+            return Vec3::new_empty();
+        }
+
+        let reflected: Vec3 = reflect(&(-self.wo), &h);
+        let f: f64 = fresnel_dielectric(cos_o, self.eta_o, self.eta_i);
+        if random_double(rng) < f {
+            return reflected;
+        }
+
+        let ratio: f64 = self.eta_o / self.eta_i;
+        let sin2_t: f64 = ratio*ratio*(1.0 - cos_o*cos_o);
+        if sin2_t >= 1.0 {
+            return reflected;
+        }
+        F64Multiplier(-ratio)*self.wo + F64Multiplier(ratio*cos_o - (1.0 - sin2_t).sqrt())*h
+    }
+}
+
+// Microfacet normal that refracts wo into wi, on the side of n, along with the
+// denominator wo.h + eta wi.h shared by the BTDF and its pdf. None if no facet
+// can refract between the two directions.
+pub fn transmission_half_vector(wo: Vec3, wi: Vec3, n: Vec3, eta_o: f64, eta_i: f64) -> Option<(Vec3, f64)> {
+    let eta: f64 = eta_i / eta_o;
+    let mut h: Vec3 = wo + F64Multiplier(eta)*wi;
+    if h.near_zero() {
+        return None;
+    }
+    h = h.unit_vector();
+    if h.dot(n) < 0.0 {
+        h = -h;
+    }
+
+    // wo and wi have to be on opposite sides of the microfacet
+    if wo.dot(h) <= 0.0 || wi.dot(h) >= 0.0 {
+        return None;
+    }
+
+    let denom: f64 = wo.dot(h) + eta*wi.dot(h);
+    if denom == 0.0 {
+        return None;
+    }
+    Some((h, denom))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{pdfs::pdf::tests::{integrate, fraction_generated, assert_generate_matches_value}, utils::new_rng, vec3::Vec3};
+
+    use super::RoughDielectricPdf;
+
+    // Into glass and out of it, where some directions are totally internally
+    // reflected. The normal is along x so the refracted lobes, which are narrower
+    // than the reflected ones, stay away from the poles of the integration grid.
+    fn pdfs() -> Vec<RoughDielectricPdf> {
+        let normal: Vec3 = Vec3::new(1.0, 0.0, 0.0);
+        let mut pdfs: Vec<RoughDielectricPdf> = Vec::new();
+        for (eta_o, eta_i) in [(1.0, 1.5), (1.5, 1.0)] {
+            for alpha in [0.3, 0.6, 1.0] {
+                for wo in [Vec3::new(1.0, 0.0, 0.0), Vec3::new(1.0, 0.5, 1.0), Vec3::new(0.2, 1.0, 0.0)] {
+                    pdfs.push(RoughDielectricPdf::new(normal, wo, alpha, eta_o, eta_i));
+                }
+            }
+        }
+        pdfs
+    }
+
+    #[test]
+    fn value_integrates_to_one() {
+        // Reflections and refractions both count, on whichever side they end up
+        for (eta_o, eta_i) in [(1.0, 1.5), (1.5, 1.0)] {
+            for alpha in [0.3, 0.6, 1.0] {
+                let normal: Vec3 = Vec3::new(1.0, 0.0, 0.0);
+                let integral: f64 = integrate(&RoughDielectricPdf::new(normal, normal, alpha, eta_o, eta_i));
+                assert!((integral - 1.0).abs() < 1e-3, "{}", integral);
+            }
+        }
+    }
+
+    #[test]
+    fn value_integrates_to_the_directions_generated() {
+        // Facets facing away from wo give no direction, there are more of them
+        // the closer wo is to the surface
+        for pdf in pdfs() {
+            let integral: f64 = integrate(&pdf);
+            let generated: f64 = fraction_generated(&pdf, &mut new_rng(0, 0));
+            assert!((integral - generated).abs() < 0.005, "value integrates to {}, {} generated", integral, generated);
+        }
+    }
+
+    #[test]
+    fn generate_matches_value() {
+        for pdf in pdfs() {
+            assert_generate_matches_value(&pdf, &mut new_rng(0, 0), 0.005);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,19 @@
+use std::f64::consts::PI;
+
+use crate::vec3::{Vec3, sample_unit_sphere};
+use crate::utils::RenderRng;
+
+use super::pdf::Pdf;
+
+// All directions equally likely
+pub struct SpherePdf;
+
+impl Pdf for SpherePdf {
+    fn value(&self, _direction: &Vec3) -> f64 {
+        1.0 / (4.0*PI)
+    }
+
+    fn generate(&self, u: [f64; 2], _rng: &mut RenderRng) -> Vec3 {
+        sample_unit_sphere(u)
+    }
+}
\ No newline at end of file
@@ -3,6 +3,8 @@ use crate::vec3::{Point3, Vec3, F64Multiplier};
 pub struct Ray {
     orig: Point3,
     dir: Vec3,
+    // Moment within the camera shutter interval the ray was sent at
+    tm: f64,
 }
 
 impl Ray {
@@ -10,14 +12,16 @@ impl Ray {
     pub fn new_empty() -> Ray {
         Ray {
             orig: Point3::new_empty(),
-            dir: Vec3::new_empty()
+            dir: Vec3::new_empty(),
+            tm: 0.0
         }
     }
 
-    pub fn new(orig: Point3, dir: Vec3) -> Ray {
+    pub fn new(orig: Point3, dir: Vec3, tm: f64) -> Ray {
         Ray {
             orig,
-            dir
+            dir,
+            tm
         }
     }
 
@@ -29,6 +33,10 @@ impl Ray {
         self.dir
     }
 
+    pub fn time(&self) -> f64 {
+        self.tm
+    }
+
     pub fn at(&self, t: f64) -> Point3 {
         self.orig + F64Multiplier(t)*self.dir
     }
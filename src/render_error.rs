@@ -0,0 +1,61 @@
+use std::{fmt::{self, Display}, error::Error, io};
+
+use crate::{image_output::OutputError, render_settings::SettingsError, scene::scene_error::SceneError};
+
+// Everything that can stop a render from producing an image
+#[derive(Debug)]
+pub enum RenderError {
+    // The scene file could not be loaded
+    Scene(SceneError),
+    // The camera can't produce an image, e.g. it looks at its own position
+    Camera { field: String, message: String },
+    Settings(SettingsError),
+    // The number of cores could not be determined, pass the number of threads explicitly
+    Parallelism(io::Error),
+    // A render thread panicked, the message is the panic payload when it is a string
+    WorkerPanicked(String),
+    // Writing the progress to stdout failed
+    Progress(io::Error),
+    Output { file: String, source: OutputError },
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::Scene(ex) => write!(f, "{}", ex),
+            RenderError::Camera { field, message } =>
+                write!(f, "Invalid camera setting `{}`: {}", field, message),
+            RenderError::Settings(ex) => write!(f, "{}", ex),
+            RenderError::Parallelism(ex) =>
+                write!(f, "Error getting the number of available cores, set the number of threads instead: {}", ex),
+            RenderError::WorkerPanicked(message) => write!(f, "Render thread panicked: {}", message),
+            RenderError::Progress(ex) => write!(f, "Error writing render progress: {}", ex),
+            RenderError::Output { file, source } => write!(f, "Error saving image {}: {}", file, source),
+        }
+    }
+}
+
+impl Error for RenderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RenderError::Scene(ex) => Some(ex),
+            RenderError::Settings(ex) => Some(ex),
+            RenderError::Parallelism(ex) => Some(ex),
+            RenderError::Progress(ex) => Some(ex),
+            RenderError::Output { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<SceneError> for RenderError {
+    fn from(ex: SceneError) -> Self {
+        RenderError::Scene(ex)
+    }
+}
+
+impl From<SettingsError> for RenderError {
+    fn from(ex: SettingsError) -> Self {
+        RenderError::Settings(ex)
+    }
+}
\ No newline at end of file
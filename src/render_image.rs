@@ -1,178 +1,570 @@
-use std::{io::{self, Write}, thread::{available_parallelism, JoinHandle, self}, sync::{Arc, Mutex}};
-
-use image::{RgbImage, ImageBuffer, Rgb};
+use std::{any::Any, io::{self, Write}, ops::Range, time::{Duration, Instant}, thread::{available_parallelism, self}, sync::{Arc, atomic::{AtomicUsize, Ordering}}};
 
 use crate::{
-    hittables::{hittable_list::HittableList,sphere::Sphere},
-    vec3::{Point3},
+    backgrounds::background::Background,
+    hittables::{hittable::Hittable, hittable_list::HittableList, bvh::BvhNode},
+    scene::scene_loader::{Scene, build_camera},
     camera::Camera,
+    render_settings::RenderSettings,
+    render_error::RenderError,
+    framebuffer::FrameBuffer,
+    accumulator::{Accumulator, PixelSamples},
+    tile_scheduler::{Tile, TileQueue},
     ray::Ray,
-    utils::random_double,
-    rgb_wrapper::RgbWrapper,
-    color::{write_color, ray_color}
+    utils::{RenderRng, new_rng},
+    samplers::sampler::{Sampler, new_sampler},
+    color::{TraceScene, ray_color}
 };
 
-const SAMPLES_PER_PIXEL: u32 = 100;
-const VIEWPORT_HEIGHT: f64 = 2.0;
-const FOCAL_LENGTH: f64 = 1.0;
-const MAX_DEPTH: u32 = 50;
-const GAMMA: f64 = 2.0;
+// A tile and its pixels, row by row
+type RenderedTile = (Tile, Vec<PixelSamples>);
+
+// Why a progressive render stopped
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    Samples,
+    TimeLimit,
+    NoiseThreshold,
+}
 
-pub fn render_image(out_file: String, image_width: u32, aspect_ratio: f64, mt: bool) {
-    let image_height: u32 = (image_width as f64 / aspect_ratio) as u32;
+// State of a progressive render after one of its passes
+#[derive(Clone, Copy, Debug)]
+pub struct PassReport {
+    // Counts from zero
+    pub pass: u32,
+    // Average samples per pixel so far
+    pub samples_per_pixel: f64,
+    // Pixels that got samples in this pass
+    pub pixels_sampled: usize,
+    pub elapsed: Duration,
+    // Average relative error of the pixels, see Accumulator::noise
+    pub noise: Option<f64>,
+    // Set on the last pass
+    pub stop: Option<StopReason>,
+}
 
-    let mut image: RgbImage = ImageBuffer::new(image_width, image_height);
+// Renders a scene into an in-memory framebuffer. The scene's objects are put
+// into a bounding volume hierarchy once, up front, and shared by all the threads.
+pub struct Renderer<'a> {
+    world: Arc<dyn Hittable + Send + Sync>,
+    lights: &'a HittableList,
+    media: &'a HittableList,
+    background: &'a (dyn Background + Send + Sync),
+    camera: Camera,
+    settings: RenderSettings,
+    show_progress: bool,
+}
 
-    let mut world: HittableList = HittableList::new_empty();
-    world.add(Arc::new(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5)));
-    world.add(Arc::new(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0)));
+impl<'a> Renderer<'a> {
+    pub fn new(scene: &'a Scene, settings: RenderSettings) -> Self {
+        Self {
+            world: Arc::new(BvhNode::new(&scene.world)),
+            lights: &scene.lights,
+            media: &scene.media,
+            background: scene.background.as_ref(),
+            camera: build_camera(&scene.camera, settings.aspect_ratio),
+            settings,
+            show_progress: false
+        }
+    }
 
-    // Camera
-    let cam: Camera = Camera::new(aspect_ratio, VIEWPORT_HEIGHT, FOCAL_LENGTH);
+    // Prints the number of scanlines or tiles left to stdout while rendering
+    pub fn show_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
 
-    if mt {
-        multithreaded_render(&out_file, &mut image, &world, &cam);
-    } else {
-        single_threaded_render(&out_file, &mut image, &world, &cam);
+    pub fn settings(&self) -> &RenderSettings {
+        &self.settings
     }
-}
 
-fn single_threaded_render(out_file: &String, image: &mut RgbImage, world: &HittableList, cam: &Camera) {
-    let image_width = image.width();
-    let image_height = image.height();
-
-    for j in (0..image_height).rev() {
-        print!("\rScanlines remanining: {}", j);
-        if let Err(e) = io::stdout().flush() {
-            panic!("Error with flushing stdout: {}", e);
-        }
-        for i in 0..image_width {
-            let mut pixel_color: RgbWrapper = RgbWrapper(Rgb::from([0.0, 0.0, 0.0]));
-            for _ in 0..SAMPLES_PER_PIXEL {
-                let u: f64 = (i as f64 + random_double()) / (image_width - 1) as f64;
-                let v: f64 = (j as f64 + random_double()) / (image_height - 1) as f64;
-                let r: Ray = cam.get_ray(u, v);
-                pixel_color += ray_color(&r, world, MAX_DEPTH);
-            }
-            // image buffer starts from the bottom left, so we have to convert
-            let pixel_x = (image_width - 1) - i;
-            let pixel_y = (image_height - 1) - j; 
-            image.put_pixel(pixel_x, pixel_y, write_color(pixel_color.0, SAMPLES_PER_PIXEL, GAMMA));
+    pub fn render(&self, multithread: bool) -> Result<FrameBuffer, RenderError> {
+        Ok(self.render_samples(multithread)?.to_framebuffer())
+    }
+
+    // Like render, but keeps every pixel's samples, e.g. to see where adaptive
+    // sampling spent them
+    pub fn render_samples(&self, multithread: bool) -> Result<Accumulator, RenderError> {
+        // Adaptive sampling has to look at the pixels between passes
+        if self.settings.adaptive_threshold.is_some() {
+            return self.render_progressive(multithread, |_, _| Ok(()));
         }
+
+        self.settings.validate()?;
+        let mut accumulator: Accumulator = Accumulator::new(self.settings.image_width, self.settings.image_height());
+
+        let samples_per_pixel: u32 = self.settings.samples_per_pixel;
+        self.run_pass(&mut accumulator, 0, &|_, _| 0..samples_per_pixel, multithread, self.show_progress)?;
+
+        Ok(accumulator)
     }
 
-    match image.save(&out_file) {
-        Err(ex) => panic!("Error with saving image: {}", ex),
-        Ok(_) => println!("\nImage saved to file: {}", out_file)
+    // Renders in passes of settings.samples_per_pass samples, until every pixel has
+    // settings.samples_per_pixel of them, the time limit is up or the image is less
+    // noisy than the noise threshold. With adaptive sampling, pixels stop getting
+    // samples once they are less noisy than the adaptive threshold, and the render
+    // finishes when none are left. on_pass sees the image after every pass.
+    pub fn render_progressive<F>(&self, multithread: bool, mut on_pass: F) -> Result<Accumulator, RenderError>
+    where
+        F: FnMut(&Accumulator, &PassReport) -> Result<(), RenderError>
+    {
+        self.settings.validate()?;
+        let mut accumulator: Accumulator = Accumulator::new(self.settings.image_width, self.settings.image_height());
+        let start: Instant = Instant::now();
+
+        let mut budget: Vec<Range<u32>> = self.sample_budget(&accumulator);
+        for pass in 0.. {
+            let image_width: u32 = accumulator.width();
+            let pixels_sampled: usize = budget.iter().filter(|samples| !samples.is_empty()).count();
+            self.run_pass(&mut accumulator, pass, &|x, y| budget[(y * image_width + x) as usize].clone(), multithread, false)?;
+            budget = self.sample_budget(&accumulator);
+
+            let elapsed: Duration = start.elapsed();
+            let noise: Option<f64> = accumulator.noise();
+            let samples_per_pixel: f64 = accumulator.average_count();
+            let stop: Option<StopReason> = if budget.iter().all(|samples| samples.is_empty()) {
+                Some(StopReason::Samples)
+            } else if self.settings.time_limit.is_some_and(|limit| elapsed.as_secs_f64() >= limit) {
+                Some(StopReason::TimeLimit)
+            } else if let (Some(noise), Some(threshold)) = (noise, self.settings.noise_threshold) {
+                if noise <= threshold { Some(StopReason::NoiseThreshold) } else { None }
+            } else {
+                None
+            };
+
+            let report: PassReport = PassReport { pass, samples_per_pixel, pixels_sampled, elapsed, noise, stop };
+            if self.show_progress {
+                print!("\rPass {}: {:.1} samples per pixel, {} pixels sampled, noise {}, {:.1}s  ", pass + 1, samples_per_pixel,
+                    pixels_sampled, noise.map_or("-".to_string(), |noise| format!("{:.4}", noise)), elapsed.as_secs_f64());
+                io::stdout().flush().map_err(RenderError::Progress)?;
+            }
+            on_pass(&accumulator, &report)?;
+
+            if stop.is_some() {
+                break;
+            }
+        }
+
+        Ok(accumulator)
     }
-}
 
-fn multithreaded_render(out_file: &String, image: &mut RgbImage, world: &HittableList, cam: &Camera) {
-    let num_threads = get_num_threads();
+    // Samples each pixel takes in the next pass, row by row. They carry on from
+    // the ones the pixel already has.
+    fn sample_budget(&self, accumulator: &Accumulator) -> Vec<Range<u32>> {
+        let settings: &RenderSettings = &self.settings;
+        let mut budget: Vec<Range<u32>> = Vec::with_capacity((accumulator.width() * accumulator.height()) as usize);
+
+        for y in 0..accumulator.height() {
+            for x in 0..accumulator.width() {
+                let count: u32 = accumulator.get_pixel(x, y).count;
+                let remaining: u32 = settings.samples_per_pixel.saturating_sub(count);
+
+                let samples: u32 = match settings.adaptive_threshold {
+                    None => settings.samples_per_pass,
+                    // Every pixel starts with enough samples to tell how noisy it is
+                    Some(_) if count < settings.adaptive_min_samples => settings.adaptive_min_samples - count,
+                    Some(threshold) => match accumulator.relative_error(x, y) {
+                        Some(error) if error <= threshold => 0,
+                        _ => settings.samples_per_pass,
+                    },
+                };
+                budget.push(count..count + samples.min(remaining));
+            }
+        }
 
-    let image_width = image.width();
-    let image_height = image.height();
+        budget
+    }
 
-    let local_image = image.clone();
+    // Adds the samples numbered samples(x, y) to every pixel of the accumulator.
+    // The numbers pick the sampler's points, so passes should carry on from the
+    // samples a pixel already has. Each pass draws different random numbers,
+    // passes with the same number give the same samples.
+    pub fn render_pass(&self, accumulator: &mut Accumulator, pass: u32, samples: &(dyn Fn(u32, u32) -> Range<u32> + Sync), multithread: bool) -> Result<(), RenderError> {
+        self.run_pass(accumulator, pass, samples, multithread, false)
+    }
 
-    let thread_chunks = determine_thread_chunks(num_threads, image_width, image_height);
+    fn run_pass(&self, accumulator: &mut Accumulator, pass: u32, samples: &(dyn Fn(u32, u32) -> Range<u32> + Sync), multithread: bool, show_progress: bool) -> Result<(), RenderError> {
+        if multithread {
+            self.multithreaded_render(accumulator, pass, samples, show_progress)
+        } else {
+            self.single_threaded_render(accumulator, pass, samples, show_progress)
+        }
+    }
 
-    let mut threads: Vec<JoinHandle<i32>> = Vec::new();
+    // x and y are image buffer coordinates, which start from the top left.
+    // Returns the sum of the linear colors of the samples.
+    fn sample_pixel(&self, x: u32, y: u32, pass: u32, samples: Range<u32>, image_width: u32, image_height: u32) -> PixelSamples {
+        let i: u32 = x;
+        let j: u32 = (image_height - 1) - y;
+
+        // Each pixel has its own stream of random numbers in each pass
+        let pixel_index: u64 = (y as u64) * image_width as u64 + x as u64;
+        let rng: RenderRng = new_rng(self.settings.seed, ((pass as u64) << 32) | pixel_index);
+        let mut sampler: Box<dyn Sampler> = new_sampler(self.settings.sampler, self.settings.seed, x, y, self.settings.samples_per_pixel, rng);
+
+        let scene: TraceScene = TraceScene {
+            world: self.world.as_ref(),
+            lights: self.lights,
+            media: self.media,
+            background: self.background
+        };
+
+        let mut pixel_samples: PixelSamples = PixelSamples::default();
+        for index in samples {
+            sampler.start_sample(index);
+            let offset: [f64; 2] = sampler.next_2d();
+            let u: f64 = (i as f64 + offset[0]) / (image_width - 1) as f64;
+            let v: f64 = (j as f64 + offset[1]) / (image_height - 1) as f64;
+            let lens: [f64; 2] = sampler.next_2d();
+            let time: f64 = sampler.next_1d();
+            let r: Ray = self.camera.get_ray(u, v, lens, time);
+            pixel_samples.add(ray_color(&r, &scene, self.settings.max_depth, sampler.as_mut()).0);
+        }
 
-    let image_mutex = Arc::new(Mutex::new(local_image));
+        pixel_samples
+    }
 
-    for thread_num in 0..num_threads {
-        let chunk = thread_chunks.get(thread_num as usize).unwrap();
-        let x_start = chunk.0;
-        let x_end = chunk.1;
-        let y_start = chunk.2;
-        let y_end = chunk.3;
+    fn single_threaded_render(&self, accumulator: &mut Accumulator, pass: u32, samples: &(dyn Fn(u32, u32) -> Range<u32> + Sync), show_progress: bool) -> Result<(), RenderError> {
+        let (image_width, image_height) = (accumulator.width(), accumulator.height());
 
-        let thread_world = world.clone();
-        let thread_cam = cam.clone();
+        for y in 0..image_height {
+            if show_progress {
+                print!("\rScanlines remanining: {}", image_height - 1 - y);
+                io::stdout().flush().map_err(RenderError::Progress)?;
+            }
+            for x in 0..image_width {
+                let pixel_samples: PixelSamples = self.sample_pixel(x, y, pass, samples(x, y), image_width, image_height);
+                accumulator.add(x, y, &pixel_samples);
+            }
+        }
 
-        let thread_image_mutex = Arc::clone(&image_mutex);
+        Ok(())
+    }
 
-        let thread = thread::spawn(move || {
-            for j in (y_start..y_end).rev() {
-                for i in x_start..x_end {
-                    let mut pixel_color: RgbWrapper = RgbWrapper(Rgb::from([0.0, 0.0, 0.0]));
-                    for _ in 0..SAMPLES_PER_PIXEL {
-                        let u: f64 = (i as f64 + random_double()) / (image_width - 1) as f64;
-                        let v: f64 = (j as f64 + random_double()) / (image_height - 1) as f64;
-                        let r: Ray = thread_cam.get_ray(u, v);
-                        pixel_color += ray_color(&r, &thread_world, MAX_DEPTH);
+    // Workers pull tiles from a shared queue and render each into its own buffer,
+    // the buffers are added to the accumulator once all the workers are done
+    fn multithreaded_render(&self, accumulator: &mut Accumulator, pass: u32, samples: &(dyn Fn(u32, u32) -> Range<u32> + Sync), show_progress: bool) -> Result<(), RenderError> {
+        let num_threads: u32 = get_num_threads(&self.settings)?;
+        let (image_width, image_height) = (accumulator.width(), accumulator.height());
+
+        let queue: TileQueue = TileQueue::new(image_width, image_height, self.settings.tile_size);
+        let tiles_done: AtomicUsize = AtomicUsize::new(0);
+
+        let rendered_tiles: Vec<RenderedTile> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..num_threads).map(|_| {
+                scope.spawn(|| -> Result<Vec<RenderedTile>, RenderError> {
+                    let mut rendered: Vec<RenderedTile> = Vec::new();
+
+                    while let Some(tile) = queue.next_tile() {
+                        let mut buffer: Vec<PixelSamples> = Vec::with_capacity(tile.pixel_count());
+                        for y in tile.y_start..tile.y_end {
+                            for x in tile.x_start..tile.x_end {
+                                buffer.push(self.sample_pixel(x, y, pass, samples(x, y), image_width, image_height));
+                            }
+                        }
+                        rendered.push((tile, buffer));
+
+                        let done: usize = tiles_done.fetch_add(1, Ordering::Relaxed) + 1;
+                        if show_progress {
+                            print!("\rTiles remaining: {}  ", queue.len() - done);
+                            io::stdout().flush().map_err(RenderError::Progress)?;
+                        }
                     }
-                    // image buffer starts from the bottom left, so we have to convert
-                    let pixel_x = (image_width - 1) - i;
-                    let pixel_y = (image_height - 1) - j;
 
-                    let mut image_changer = match thread_image_mutex.lock() {
-                        Err(ex) => panic!("Error locking mutex for image: {:?}", ex),
-                        Ok(mutex) => mutex,
-                    };
-
-                    image_changer.put_pixel(pixel_x, pixel_y, write_color(pixel_color.0, SAMPLES_PER_PIXEL, GAMMA));
+                    Ok(rendered)
+                })
+            }).collect();
+
+            // Join every worker before reporting the first failure
+            let results: Vec<Result<Vec<RenderedTile>, RenderError>> = workers.into_iter()
+                .map(|worker| match worker.join() {
+                    Err(payload) => Err(RenderError::WorkerPanicked(panic_message(payload.as_ref()))),
+                    Ok(rendered) => rendered,
+                })
+                .collect();
+            results.into_iter().collect::<Result<Vec<_>, RenderError>>()
+        })?.into_iter().flatten().collect();
+
+        for (tile, buffer) in rendered_tiles {
+            let mut pixels = buffer.iter();
+            for y in tile.y_start..tile.y_end {
+                for x in tile.x_start..tile.x_end {
+                    if let Some(pixel_samples) = pixels.next() {
+                        accumulator.add(x, y, pixel_samples);
+                    }
                 }
             }
-            0
-        });
+        }
 
-        threads.push(thread);
+        Ok(())
     }
+}
 
-    for thread in threads {
-        thread.join().unwrap();
+fn get_num_threads(settings: &RenderSettings) -> Result<u32, RenderError> {
+    if let Some(threads) = settings.threads {
+        return Ok(threads);
     }
 
-    let image_to_save = match image_mutex.lock() {
-        Err(ex) => panic!("Error getting image lock: {:?}", ex),
-        Ok(image_lock) => image_lock,
-    };
-
-    match image_to_save.save(&out_file) {
-        Err(ex) => panic!("Error with saving image: {}", ex),
-        Ok(_) => println!("\nImage saved to file: {}", out_file)
+    match available_parallelism() {
+        Ok(n) => Ok(n.get() as u32),
+        Err(ex) => Err(RenderError::Parallelism(ex)),
     }
 }
 
-fn get_num_threads() -> u32 {
-    match available_parallelism() {
-        Ok(n) => n.get() as u32,
-        Err(ex) => panic!("Error getting available parallelism: {:?}", ex),
+// Panics usually carry a &str or a String
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown error".to_string()
     }
 }
 
-// Chunks are a tuple of (x_start, x_end, y_start, y_end)
-fn determine_thread_chunks(num_threads: u32, image_width: u32, image_height: u32) -> Vec<(u32, u32, u32, u32)> {
-    let mut chunks: Vec<(u32, u32, u32, u32)> = Vec::new();
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf, sync::Arc};
+
+    use image::Rgb;
+
+    use crate::{
+        accumulator::Accumulator,
+        backgrounds::{constant::Constant, gradient::Gradient},
+        hittables::{aabb::Aabb, hittable::{Hittable, HitRecord}, hittable_list::HittableList, sphere::Sphere},
+        materials::{lambertian::Lambertian, material::Material},
+        ray::Ray,
+        render_error::RenderError,
+        render_settings::RenderSettings,
+        samplers::sampler::SamplerKind,
+        scene::{scene_description::{CameraDescription, RenderDescription}, scene_error::SceneError, scene_loader::{Scene, load_scene}},
+        vec3::Point3};
+
+    use super::{PassReport, Renderer, StopReason};
+
+    // Nothing but a uniform background, every sample of a pixel is the same
+    fn flat_scene() -> Scene {
+        Scene {
+            world: HittableList::new_empty(),
+            lights: HittableList::new_empty(),
+            media: HittableList::new_empty(),
+            camera: CameraDescription::default(),
+            background: Arc::new(Constant::new(Rgb::from([0.5, 0.5, 0.5]))),
+            render: RenderDescription::default()
+        }
+    }
+
+    // Only lambertian surfaces, which scatter without the random number generator
+    fn diffuse_scene() -> Scene {
+        let mut world: HittableList = HittableList::new_empty();
+        let material_ground: Material = Material::new(Arc::new(Lambertian::new(Rgb::from([0.8, 0.8, 0.0]))));
+        let material_center: Material = Material::new(Arc::new(Lambertian::new(Rgb::from([0.1, 0.2, 0.5]))));
+        world.add(Arc::new(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, material_ground)));
+        world.add(Arc::new(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, material_center)));
+
+        Scene {
+            world,
+            lights: HittableList::new_empty(),
+            media: HittableList::new_empty(),
+            camera: CameraDescription::default(),
+            background: Arc::new(Gradient::default()),
+            render: RenderDescription::default()
+        }
+    }
+
+    fn settings() -> RenderSettings {
+        RenderSettings {
+            image_width: 16,
+            aspect_ratio: 2.0,
+            samples_per_pixel: 10,
+            max_depth: 8,
+            threads: Some(2),
+            tile_size: 4,
+            seed: 1,
+            samples_per_pass: 4,
+            ..RenderSettings::default()
+        }
+    }
+
+    fn render_progressive(scene: &Scene, settings: RenderSettings) -> (Accumulator, Vec<PassReport>) {
+        let mut reports: Vec<PassReport> = Vec::new();
+        let accumulator: Accumulator = Renderer::new(scene, settings).render_progressive(true, |_, report| {
+            reports.push(*report);
+            Ok(())
+        }).unwrap();
+        (accumulator, reports)
+    }
+
+    fn counts(accumulator: &Accumulator) -> Vec<u32> {
+        (0..accumulator.height())
+            .flat_map(|y| (0..accumulator.width()).map(move |x| (x, y)))
+            .map(|(x, y)| accumulator.get_pixel(x, y).count)
+            .collect()
+    }
 
-    let num_threads_x = determine_num_threads_x(num_threads);
-    let num_threads_y = num_threads / num_threads_x;
-    let x_steps = (image_width as f64 / num_threads_x as f64).ceil() as u32;
-    let y_steps = (image_height as f64 / num_threads_y as f64).ceil() as u32;
+    #[test]
+    fn passes_add_up_to_samples_per_pixel() {
+        let scene: Scene = diffuse_scene();
+        let (accumulator, reports) = render_progressive(&scene, settings());
 
-    for j in (0..image_height).step_by(y_steps as usize) {
-        let y_start = j;
-        let y_end = if j + y_steps > image_height { image_height } else { j + y_steps };
-        for i in (0..image_width).step_by(x_steps as usize) {
-            let x_start = i;
-            let x_end = if i + x_steps > image_width { image_width } else { i + x_steps };
-            chunks.push((x_start, x_end, y_start, y_end));
+        // 4 + 4 + 2 samples
+        assert_eq!(reports.len(), 3);
+        for (pass, report) in reports.iter().enumerate() {
+            assert_eq!(report.pass, pass as u32);
+            assert_eq!(report.pixels_sampled, 16 * 8);
         }
+        assert_eq!(reports[0].stop, None);
+        assert_eq!(reports[1].stop, None);
+        assert_eq!(reports[2].stop, Some(StopReason::Samples));
+        assert_eq!(reports[2].samples_per_pixel, 10.0);
+        assert!(counts(&accumulator).iter().all(|&count| count == 10));
     }
 
-    chunks
-}
+    #[test]
+    fn time_limit_stops_after_the_pass_that_runs_over() {
+        let scene: Scene = diffuse_scene();
+        let (accumulator, reports) = render_progressive(&scene, RenderSettings {
+            samples_per_pixel: 100,
+            time_limit: Some(1e-9),
+            ..settings()
+        });
 
-fn determine_num_threads_x(num_threads: u32) -> u32 {
-    let nt_sqrt = (num_threads as f64).sqrt().ceil() as u32;
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].stop, Some(StopReason::TimeLimit));
+        assert!(counts(&accumulator).iter().all(|&count| count == 4));
+    }
+
+    #[test]
+    fn noise_threshold_stops_a_flat_image_early() {
+        let scene: Scene = flat_scene();
+        let (accumulator, reports) = render_progressive(&scene, RenderSettings {
+            samples_per_pixel: 100,
+            noise_threshold: Some(0.01),
+            ..settings()
+        });
 
-    for i in (0..nt_sqrt).rev() {
-        if (num_threads as f64 / i as f64).fract() == 0.0 {
-            return i as u32;
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].stop, Some(StopReason::NoiseThreshold));
+        assert!(reports[0].noise.unwrap() <= 0.01);
+        assert!(counts(&accumulator).iter().all(|&count| count == 4));
+    }
+
+    #[test]
+    fn passes_match_a_single_pass_render() {
+        // The sampler's points don't depend on the pass, only the random number
+        // generator does, and nothing in the scene uses it
+        let scene: Scene = diffuse_scene();
+        let settings: RenderSettings = RenderSettings { sampler: SamplerKind::Sobol, ..settings() };
+
+        let (progressive, reports) = render_progressive(&scene, settings);
+        let single: Accumulator = Renderer::new(&scene, settings).render_samples(true).unwrap();
+
+        assert_eq!(reports.len(), 3);
+        for y in 0..single.height() {
+            for x in 0..single.width() {
+                assert_eq!(progressive.get_pixel(x, y).count, single.get_pixel(x, y).count);
+                let (a, b) = (progressive.mean(x, y), single.mean(x, y));
+                for channel in 0..3 {
+                    assert!((a[channel] - b[channel]).abs() < 1e-12, "pixel ({}, {}): {:?} != {:?}", x, y, a, b);
+                }
+            }
         }
     }
 
-    panic!("No way to evenly split num threads on x")
+    #[test]
+    fn adaptive_stops_flat_pixels_at_the_minimum_samples() {
+        let scene: Scene = flat_scene();
+        let accumulator: Accumulator = Renderer::new(&scene, RenderSettings {
+            samples_per_pixel: 64,
+            adaptive_threshold: Some(0.01),
+            adaptive_min_samples: 8,
+            ..settings()
+        }).render_samples(true).unwrap();
+
+        assert!(counts(&accumulator).iter().all(|&count| count == 8));
+    }
+
+    #[test]
+    fn adaptive_samples_noisy_pixels_up_to_samples_per_pixel() {
+        let scene: Scene = diffuse_scene();
+        let accumulator: Accumulator = Renderer::new(&scene, RenderSettings {
+            samples_per_pixel: 32,
+            adaptive_threshold: Some(1e-6),
+            adaptive_min_samples: 8,
+            ..settings()
+        }).render_samples(true).unwrap();
+
+        // Even the sky varies a little across a pixel, nothing gets this quiet
+        assert!(counts(&accumulator).iter().all(|&count| count == 32));
+    }
+
+    // Object that panics when a ray is traced against it
+    struct Broken;
+
+    impl Hittable for Broken {
+        fn hit(&self, _r: &Ray, _t_min: f64, _t_max: f64, _rec: &mut HitRecord) -> bool {
+            panic!("broken object");
+        }
+
+        fn bounding_box(&self) -> Aabb {
+            Aabb::new(Point3::new(-1.0, -1.0, -2.0), Point3::new(1.0, 1.0, 0.0))
+        }
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let scene: Scene = flat_scene();
+        let invalid: [(&str, RenderSettings); 4] = [
+            ("image_width", RenderSettings { image_width: 1, ..settings() }),
+            ("samples_per_pixel", RenderSettings { samples_per_pixel: 0, ..settings() }),
+            ("threads", RenderSettings { threads: Some(0), ..settings() }),
+            ("adaptive_threshold", RenderSettings { adaptive_threshold: Some(-1.0), ..settings() })];
+
+        for (setting, settings) in invalid {
+            let renderer: Renderer = Renderer::new(&scene, settings);
+            for result in [renderer.render(false).map(|_| ()), renderer.render_progressive(true, |_, _| Ok(())).map(|_| ())] {
+                match result {
+                    Err(RenderError::Settings(ex)) => assert_eq!(ex.setting, setting),
+                    other => panic!("expected {} to be rejected, got {:?}", setting, other),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn panicking_render_thread_is_reported() {
+        let mut scene: Scene = flat_scene();
+        scene.world.add(Arc::new(Broken));
+
+        match Renderer::new(&scene, settings()).render(true) {
+            Err(RenderError::WorkerPanicked(message)) => assert_eq!(message, "broken object"),
+            other => panic!("expected the panic to be reported, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn on_pass_errors_stop_the_render() {
+        let scene: Scene = flat_scene();
+        let mut passes: u32 = 0;
+        let result = Renderer::new(&scene, settings()).render_progressive(true, |_, _| {
+            passes += 1;
+            Err(RenderError::WorkerPanicked("stop".to_string()))
+        });
+
+        assert!(matches!(result, Err(RenderError::WorkerPanicked(message)) if message == "stop"));
+        assert_eq!(passes, 1);
+    }
+
+    #[test]
+    fn broken_scene_file_is_a_scene_error() {
+        let file: PathBuf = env::temp_dir().join(format!("broken_scene_{}.json", std::process::id()));
+        fs::write(&file, r#"{ "objects": [{ "sphere": { "center": [0.0, 0.0, -1.0], "radius": 0.5, "material": "missing" } }] }"#).unwrap();
+        let result: Result<Scene, RenderError> = load_scene(file.to_str().unwrap()).map_err(RenderError::from);
+        fs::remove_file(&file).unwrap();
+
+        match result {
+            Err(RenderError::Scene(SceneError::Invalid { field, .. })) => assert_eq!(field, "objects[0].sphere.material"),
+            other => panic!("expected the missing material to be reported, got {:?}", other.map(|_| ())),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,137 @@
+use std::{fmt::{self, Display}, error::Error};
+
+use crate::samplers::sampler::SamplerKind;
+
+const DEFAULT_IMAGE_WIDTH: u32 = 400;
+const DEFAULT_ASPECT_RATIO: f64 = 16.0 / 9.0;
+const DEFAULT_SAMPLES_PER_PIXEL: u32 = 100;
+const DEFAULT_MAX_DEPTH: u32 = 50;
+const DEFAULT_GAMMA: f64 = 2.0;
+const DEFAULT_TILE_SIZE: u32 = 16;
+const DEFAULT_SAMPLES_PER_PASS: u32 = 4;
+const DEFAULT_ADAPTIVE_MIN_SAMPLES: u32 = 16;
+
+// Quality and output settings for a render, filled from the defaults, then the
+// scene file and finally the command line
+#[derive(Clone, Copy, Debug)]
+pub struct RenderSettings {
+    pub image_width: u32,
+    pub aspect_ratio: f64,
+    pub samples_per_pixel: u32,
+    pub max_depth: u32,
+    pub gamma: f64,
+    // Number of worker threads for a multithreaded render, None uses every available core
+    pub threads: Option<u32>,
+    // Width and height in pixels of the tiles handed out to the worker threads
+    pub tile_size: u32,
+    // Seed of the random numbers, the same seed and settings give the same image
+    pub seed: u64,
+    // Where the sample values for the pixel, lens, time and bounces come from
+    pub sampler: SamplerKind,
+    // Progressive renders add this many samples to every pixel in each pass
+    pub samples_per_pass: u32,
+    // Progressive renders stop after the pass that goes over this many seconds
+    pub time_limit: Option<f64>,
+    // Progressive renders stop once the average relative error of the pixels is below this
+    pub noise_threshold: Option<f64>,
+    // Turns on adaptive sampling: pixels get more samples, up to samples_per_pixel,
+    // until the standard error of their mean is below this fraction of their brightness
+    pub adaptive_threshold: Option<f64>,
+    // Samples every pixel gets before adaptive sampling judges how noisy it is
+    pub adaptive_min_samples: u32,
+}
+
+#[derive(Debug)]
+pub struct SettingsError {
+    pub setting: &'static str,
+    pub message: String,
+}
+
+impl Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid render setting `{}`: {}", self.setting, self.message)
+    }
+}
+
+impl Error for SettingsError {}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            image_width: DEFAULT_IMAGE_WIDTH,
+            aspect_ratio: DEFAULT_ASPECT_RATIO,
+            samples_per_pixel: DEFAULT_SAMPLES_PER_PIXEL,
+            max_depth: DEFAULT_MAX_DEPTH,
+            gamma: DEFAULT_GAMMA,
+            threads: None,
+            tile_size: DEFAULT_TILE_SIZE,
+            seed: 0,
+            sampler: SamplerKind::Independent,
+            samples_per_pass: DEFAULT_SAMPLES_PER_PASS,
+            time_limit: None,
+            noise_threshold: None,
+            adaptive_threshold: None,
+            adaptive_min_samples: DEFAULT_ADAPTIVE_MIN_SAMPLES
+        }
+    }
+}
+
+impl RenderSettings {
+    pub fn image_height(&self) -> u32 {
+        (self.image_width as f64 / self.aspect_ratio) as u32
+    }
+
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let error = |setting: &'static str, message: String| Err(SettingsError { setting, message });
+
+        if !(self.aspect_ratio.is_finite() && self.aspect_ratio > 0.0) {
+            return error("aspect_ratio", format!("must be a positive number, got {}", self.aspect_ratio));
+        }
+        // Pixel coordinates are divided by (size - 1), so each side needs at least two pixels
+        if self.image_width < 2 {
+            return error("image_width", format!("must be at least 2, got {}", self.image_width));
+        }
+        if self.image_height() < 2 {
+            return error("aspect_ratio", format!("gives an image height of {} for a width of {}, it must be at least 2", self.image_height(), self.image_width));
+        }
+        if self.samples_per_pixel == 0 {
+            return error("samples_per_pixel", "must be at least 1".to_string());
+        }
+        if self.max_depth == 0 {
+            return error("max_depth", "must be at least 1".to_string());
+        }
+        if !(self.gamma.is_finite() && self.gamma > 0.0) {
+            return error("gamma", format!("must be a positive number, got {}", self.gamma));
+        }
+        if self.threads == Some(0) {
+            return error("threads", "must be at least 1".to_string());
+        }
+        if self.tile_size == 0 {
+            return error("tile_size", "must be at least 1".to_string());
+        }
+        if self.samples_per_pass == 0 {
+            return error("samples_per_pass", "must be at least 1".to_string());
+        }
+        if let Some(time_limit) = self.time_limit {
+            if !(time_limit.is_finite() && time_limit > 0.0) {
+                return error("time_limit", format!("must be a positive number of seconds, got {}", time_limit));
+            }
+        }
+        if let Some(noise_threshold) = self.noise_threshold {
+            if !(noise_threshold.is_finite() && noise_threshold > 0.0) {
+                return error("noise_threshold", format!("must be a positive number, got {}", noise_threshold));
+            }
+        }
+        if let Some(adaptive_threshold) = self.adaptive_threshold {
+            if !(adaptive_threshold.is_finite() && adaptive_threshold > 0.0) {
+                return error("adaptive_threshold", format!("must be a positive number, got {}", adaptive_threshold));
+            }
+        }
+        // The noise of a pixel is estimated from the spread of its samples
+        if self.adaptive_min_samples < 2 {
+            return error("adaptive_min_samples", format!("must be at least 2, got {}", self.adaptive_min_samples));
+        }
+
+        Ok(())
+    }
+}
\ No newline at end of file
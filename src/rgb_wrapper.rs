@@ -2,6 +2,7 @@ use std::ops::{Add, Mul, AddAssign};
 
 use image::Rgb;
 
+#[derive(Clone, Copy)]
 pub struct RgbWrapper(pub Rgb<f64>);
 
 impl Add for RgbWrapper {
@@ -33,6 +34,21 @@ impl Mul<f64> for RgbWrapper {
     }
 }
 
+impl Mul for RgbWrapper {
+    type Output = Self;
+
+    fn mul(self, _rhs: Self) -> Self {
+        let lhs_rgb = self.0.0;
+        let rhs_rgb = _rhs.0.0;
+
+        Self(Rgb::from([
+            lhs_rgb[0] * rhs_rgb[0],
+            lhs_rgb[1] * rhs_rgb[1],
+            lhs_rgb[2] * rhs_rgb[2]
+        ]))
+    }
+}
+
 impl AddAssign for RgbWrapper {
     fn add_assign(&mut self, rhs: Self) {
         self.0.0[0] += rhs.0.0[0];
@@ -0,0 +1,125 @@
+use std::sync::OnceLock;
+
+use crate::utils::RenderRng;
+
+use super::{sampler::Sampler, hash::{hash, mix_bits, ONE_MINUS_EPSILON}, sobol::scrambled_sobol_2d};
+
+// Width and height of the tiled blue noise mask
+const MASK_SIZE: usize = 64;
+// Standard deviation, in pixels, of the energy void-and-cluster spreads around each point
+const MASK_SIGMA: f64 = 1.9;
+
+// The same scrambled Sobol points as SobolSampler in every pixel, shifted around
+// [0, 1) by a blue noise mask (a Cranley-Patterson rotation). Neighbouring
+// pixels get very different shifts, so what error is left looks like fine grain
+// rather than blotches, which is easier on the eye and to denoise.
+pub struct BlueNoiseSampler {
+    seed: u64,
+    x: usize,
+    y: usize,
+    index: u32,
+    dimension: u64,
+    rng: RenderRng,
+}
+
+impl BlueNoiseSampler {
+    pub fn new(seed: u64, x: u32, y: u32, rng: RenderRng) -> Self {
+        Self {
+            seed,
+            x: x as usize,
+            y: y as usize,
+            index: 0,
+            dimension: 0,
+            rng
+        }
+    }
+
+    // Shift of the pixel for one dimension. Each dimension reads the mask from a
+    // different offset so they aren't correlated.
+    fn shift(&self, dimension_seed: u64, axis: u64) -> f64 {
+        let offset: u64 = hash(&[dimension_seed, axis]);
+        let mask_x: usize = (self.x + (offset % MASK_SIZE as u64) as usize) % MASK_SIZE;
+        let mask_y: usize = (self.y + ((offset >> 32) % MASK_SIZE as u64) as usize) % MASK_SIZE;
+        blue_noise_mask()[mask_y*MASK_SIZE + mask_x]
+    }
+}
+
+impl Sampler for BlueNoiseSampler {
+    fn start_sample(&mut self, index: u32) {
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        self.next_2d()[0]
+    }
+
+    fn next_2d(&mut self) -> [f64; 2] {
+        let dimension_seed: u64 = hash(&[self.seed, self.dimension]);
+        self.dimension += 1;
+
+        let [u, v] = scrambled_sobol_2d(self.index, dimension_seed);
+        [
+            (u + self.shift(dimension_seed, 0)).fract().min(ONE_MINUS_EPSILON),
+            (v + self.shift(dimension_seed, 1)).fract().min(ONE_MINUS_EPSILON)
+        ]
+    }
+
+    fn rng(&mut self) -> &mut RenderRng {
+        &mut self.rng
+    }
+}
+
+// Tileable blue noise values in [0, 1), row by row, made the first time they're
+// needed
+fn blue_noise_mask() -> &'static [f64] {
+    static MASK: OnceLock<Vec<f64>> = OnceLock::new();
+    MASK.get_or_init(build_blue_noise_mask)
+}
+
+// Ulichney's void-and-cluster method, simplified: pixels are ranked by adding
+// them one at a time where they're furthest from the ones already added, which
+// is where the Gaussian energy the added pixels spread around the torus is
+// lowest. The rank is the pixel's value.
+fn build_blue_noise_mask() -> Vec<f64> {
+    let pixel_count: usize = MASK_SIZE*MASK_SIZE;
+
+    // Energy at each offset from an added pixel, wrapping around the edges
+    let mut kernel: Vec<f64> = Vec::with_capacity(pixel_count);
+    for dy in 0..MASK_SIZE {
+        for dx in 0..MASK_SIZE {
+            let wrapped_x: f64 = dx.min(MASK_SIZE - dx) as f64;
+            let wrapped_y: f64 = dy.min(MASK_SIZE - dy) as f64;
+            kernel.push((-(wrapped_x*wrapped_x + wrapped_y*wrapped_y) / (2.0*MASK_SIGMA*MASK_SIGMA)).exp());
+        }
+    }
+
+    // A tiny bit of noise breaks the ties between equally empty pixels, which
+    // would otherwise fill in a regular pattern
+    let mut energy: Vec<f64> = (0..pixel_count as u64).map(|i| (mix_bits(i) >> 11) as f64 * 1e-24).collect();
+    let mut mask: Vec<f64> = vec![0.0; pixel_count];
+    let mut added: Vec<bool> = vec![false; pixel_count];
+
+    for rank in 0..pixel_count {
+        let mut void: usize = 0;
+        let mut lowest: f64 = f64::INFINITY;
+        for (i, &e) in energy.iter().enumerate() {
+            if !added[i] && e < lowest {
+                lowest = e;
+                void = i;
+            }
+        }
+
+        added[void] = true;
+        mask[void] = (rank as f64 + 0.5) / pixel_count as f64;
+
+        let (void_x, void_y) = (void % MASK_SIZE, void / MASK_SIZE);
+        for (i, e) in energy.iter_mut().enumerate() {
+            let dx: usize = (i % MASK_SIZE + MASK_SIZE - void_x) % MASK_SIZE;
+            let dy: usize = (i / MASK_SIZE + MASK_SIZE - void_y) % MASK_SIZE;
+            *e += kernel[dy*MASK_SIZE + dx];
+        }
+    }
+
+    mask
+}
\ No newline at end of file
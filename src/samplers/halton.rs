@@ -0,0 +1,122 @@
+use crate::utils::{RenderRng, random_double};
+
+use super::{sampler::Sampler, hash::{hash, mix_bits, permute, ONE_MINUS_EPSILON}};
+
+// Dimension d of the Halton sequence reverses the digits of the sample index in
+// the base PRIMES[d]. Later dimensions are independent random numbers.
+const PRIMES: [u32; 64] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53,
+    59, 61, 67, 71, 73, 79, 83, 89, 97, 101, 103, 107, 109, 113, 127, 131,
+    137, 139, 149, 151, 157, 163, 167, 173, 179, 181, 191, 193, 197, 199, 211, 223,
+    227, 229, 233, 239, 241, 251, 257, 263, 269, 271, 277, 281, 283, 293, 307, 311];
+
+// The Halton sequence, Owen scrambled differently in every pixel so that the
+// pixels' errors aren't correlated
+pub struct HaltonSampler {
+    pixel_seed: u64,
+    index: u32,
+    dimension: usize,
+    rng: RenderRng,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64, x: u32, y: u32, rng: RenderRng) -> Self {
+        Self {
+            pixel_seed: hash(&[seed, x as u64, y as u64]),
+            index: 0,
+            dimension: 0,
+            rng
+        }
+    }
+
+    fn next_value(&mut self) -> f64 {
+        let dimension: usize = self.dimension;
+        self.dimension += 1;
+        if dimension >= PRIMES.len() {
+            return random_double(&mut self.rng);
+        }
+
+        scrambled_radical_inverse(PRIMES[dimension], self.index, hash(&[self.pixel_seed, dimension as u64]))
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_sample(&mut self, index: u32) {
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        self.next_value()
+    }
+
+    fn next_2d(&mut self) -> [f64; 2] {
+        [self.next_value(), self.next_value()]
+    }
+
+    fn rng(&mut self) -> &mut RenderRng {
+        &mut self.rng
+    }
+}
+
+// The digits of index in the base, mirrored about the point. Each digit is
+// permuted, with a permutation that depends on the digits before it, including
+// the leading zeros, until the digits are too small to change the result.
+fn scrambled_radical_inverse(base: u32, index: u32, seed: u64) -> f64 {
+    let inverse_base: f64 = 1.0 / base as f64;
+    let mut remaining: u32 = index;
+    let mut digit_scale: f64 = 1.0;
+    let mut prefix: u64 = 0;
+    let mut value: f64 = 0.0;
+
+    while 1.0 - (base - 1) as f64 * digit_scale < 1.0 {
+        let digit: u32 = remaining % base;
+        remaining /= base;
+
+        let permuted: u32 = permute(digit, base, mix_bits(seed ^ prefix) as u32);
+        prefix = prefix.wrapping_mul(base as u64).wrapping_add(permuted as u64);
+        digit_scale *= inverse_base;
+        value += permuted as f64 * digit_scale;
+    }
+
+    value.min(ONE_MINUS_EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{samplers::sampler::Sampler, utils::new_rng};
+
+    use super::{HaltonSampler, scrambled_radical_inverse};
+
+    // The first 2^a 3^b points of the first two dimensions, in bases 2 and 3,
+    // fall one to each cell of a 2^a by 3^b grid
+    #[test]
+    fn points_are_stratified() {
+        for (x, y) in [(0, 0), (3, 7)] {
+            let mut sampler: HaltonSampler = HaltonSampler::new(1, x, y, new_rng(0, 0));
+            for (a, b) in [(0, 0), (1, 0), (0, 1), (1, 1), (3, 2), (2, 3), (4, 4)] {
+                let (columns, rows) = (2usize.pow(a), 3usize.pow(b));
+                let mut cells: Vec<usize> = (0..(columns * rows) as u32).map(|index| {
+                    sampler.start_sample(index);
+                    let [u, v] = sampler.next_2d();
+                    (u * columns as f64) as usize * rows + (v * rows as f64) as usize
+                }).collect();
+                cells.sort_unstable();
+                cells.dedup();
+                assert_eq!(cells.len(), columns * rows, "{} by {} cells", columns, rows);
+            }
+        }
+    }
+
+    #[test]
+    fn radical_inverse_is_below_one() {
+        for base in [2, 3, 5, 311] {
+            for index in [0, 1, base - 1, base, 1000, u32::MAX] {
+                for seed in [0, 1, u64::MAX] {
+                    let value: f64 = scrambled_radical_inverse(base, index, seed);
+                    assert!((0.0..1.0).contains(&value), "base {}, index {}: {}", base, index, value);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
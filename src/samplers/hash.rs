@@ -0,0 +1,106 @@
+// Hashes for samplers that scramble or shuffle their points. They give the same
+// answer every time the same pixel and dimension come up, whatever the pass or
+// thread, without keeping any state.
+
+// Largest f64 below one, sample values are kept in [0, 1)
+pub const ONE_MINUS_EPSILON: f64 = 1.0 - f64::EPSILON / 2.0;
+
+// SplitMix64 finalizer
+pub fn mix_bits(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+pub fn hash(values: &[u64]) -> u64 {
+    values.iter().fold(0, |hash, &value| mix_bits((hash ^ value).wrapping_add(0x9e37_79b9_7f4a_7c15)))
+}
+
+// Element i of a random permutation of 0..length picked by seed, from Kensler's
+// "Correlated Multi-Jittered Sampling". Bits above the length are hashed too and
+// out of range results are hashed again until they land in it.
+pub fn permute(mut i: u32, length: u32, seed: u32) -> u32 {
+    let mut w: u32 = length.wrapping_sub(1);
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170_893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929_eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935_fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dc_b303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e50_1cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860_a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < length {
+            break;
+        }
+    }
+
+    // In 64 bits so a large seed doesn't wrap around and repeat a value
+    ((i as u64 + seed as u64) % length as u64) as u32
+}
+
+// Value in [0, 1) picked by i and seed
+pub fn random_float(i: u32, seed: u32) -> f64 {
+    let bits: u64 = hash(&[i as u64, seed as u64]);
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// Nested uniform scrambling (Owen scrambling) of the bits of value, from most to
+// least significant: whether each bit flips depends on a hash of the bits above
+// it. From Burley's "Practical Hash-based Owen Scrambling".
+pub fn owen_scramble(value: u32, seed: u32) -> u32 {
+    let mut x: u32 = value.reverse_bits();
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50_b47c);
+    x ^= x.wrapping_mul(0xb82f_1e52);
+    x ^= x.wrapping_mul(0xc7af_e638);
+    x ^= x.wrapping_mul(0x8d22_f6e6);
+    x.reverse_bits()
+}
+
+// Fraction in [0, 1) of the 32 bit fixed point value
+pub fn to_unit(bits: u32) -> f64 {
+    (bits as f64 / 4_294_967_296.0).min(ONE_MINUS_EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{permute, random_float, to_unit};
+
+    #[test]
+    fn permute_is_a_permutation() {
+        for length in [1, 2, 3, 5, 16, 17, 100, 1000] {
+            for seed in [0, 1, 0x1234_5678, u32::MAX] {
+                let mut values: Vec<u32> = (0..length).map(|i| permute(i, length, seed)).collect();
+                values.sort_unstable();
+                assert_eq!(values, (0..length).collect::<Vec<u32>>(), "length {}, seed {}", length, seed);
+            }
+        }
+    }
+
+    #[test]
+    fn values_are_below_one() {
+        assert_eq!(to_unit(0), 0.0);
+        assert_eq!(to_unit(u32::MAX), 1.0 - 1.0 / 4_294_967_296.0);
+        for i in 0..1000 {
+            let value: f64 = random_float(i, 7);
+            assert!((0.0..1.0).contains(&value), "{}", value);
+        }
+    }
+}
\ No newline at end of file
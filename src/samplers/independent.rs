@@ -0,0 +1,32 @@
+use crate::utils::{RenderRng, random_double};
+
+use super::sampler::Sampler;
+
+// Every value is a new uniform random number
+pub struct IndependentSampler {
+    rng: RenderRng,
+}
+
+impl IndependentSampler {
+    pub fn new(rng: RenderRng) -> Self {
+        Self {
+            rng
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_sample(&mut self, _index: u32) {}
+
+    fn next_1d(&mut self) -> f64 {
+        random_double(&mut self.rng)
+    }
+
+    fn next_2d(&mut self) -> [f64; 2] {
+        [random_double(&mut self.rng), random_double(&mut self.rng)]
+    }
+
+    fn rng(&mut self) -> &mut RenderRng {
+        &mut self.rng
+    }
+}
\ No newline at end of file
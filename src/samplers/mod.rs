@@ -0,0 +1,7 @@
+pub mod sampler;
+pub mod hash;
+pub mod independent;
+pub mod stratified;
+pub mod halton;
+pub mod sobol;
+pub mod blue_noise;
\ No newline at end of file
@@ -0,0 +1,128 @@
+use std::{fmt::{self, Display}, str::FromStr};
+
+use crate::utils::RenderRng;
+
+use super::{
+    independent::IndependentSampler,
+    stratified::StratifiedSampler,
+    halton::HaltonSampler,
+    sobol::SobolSampler,
+    blue_noise::BlueNoiseSampler};
+
+// Values in [0, 1) for the dimensions of the samples of one pixel. Each sample
+// takes the position in the pixel, the position on the lens and the time first,
+// then a light sample and a material sample at every bounce. Samplers other than
+// the independent one spread each dimension evenly over the pixel's samples, which
+// leaves less noise than the same number of independent random samples.
+//
+// Choices that don't fit into these dimensions, like whether glass reflects or
+// refracts, draw from rng().
+pub trait Sampler {
+    // Starts sample number index of the pixel, from its first dimension
+    fn start_sample(&mut self, index: u32);
+    fn next_1d(&mut self) -> f64;
+    fn next_2d(&mut self) -> [f64; 2];
+    fn rng(&mut self) -> &mut RenderRng;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SamplerKind {
+    // Independent uniform random numbers
+    #[default]
+    Independent,
+    // Correlated multi-jittered, one stratum per sample in every dimension
+    Stratified,
+    // Owen scrambled Halton sequence
+    Halton,
+    // Owen scrambled and shuffled Sobol points, best with power of two sample counts
+    Sobol,
+    // The same Sobol points in every pixel, offset by blue noise, so that the
+    // error left in neighbouring pixels differs as much as it can
+    BlueNoise,
+}
+
+impl SamplerKind {
+    pub const ALL: [SamplerKind; 5] = [
+        SamplerKind::Independent,
+        SamplerKind::Stratified,
+        SamplerKind::Halton,
+        SamplerKind::Sobol,
+        SamplerKind::BlueNoise];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SamplerKind::Independent => "independent",
+            SamplerKind::Stratified => "stratified",
+            SamplerKind::Halton => "halton",
+            SamplerKind::Sobol => "sobol",
+            SamplerKind::BlueNoise => "blue-noise",
+        }
+    }
+}
+
+impl Display for SamplerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for SamplerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match SamplerKind::ALL.iter().find(|kind| kind.name() == s) {
+            Some(kind) => Ok(*kind),
+            None => {
+                let names: Vec<&str> = SamplerKind::ALL.iter().map(|kind| kind.name()).collect();
+                Err(format!("unknown sampler `{}`, expected one of {}", s, names.join(", ")))
+            }
+        }
+    }
+}
+
+// Sampler for the pixel at (x, y) of a render with the given seed.
+// samples_per_pixel is the most samples the pixel will take and rng is the
+// pixel's stream of random numbers.
+pub fn new_sampler(kind: SamplerKind, seed: u64, x: u32, y: u32, samples_per_pixel: u32, rng: RenderRng) -> Box<dyn Sampler> {
+    match kind {
+        SamplerKind::Independent => Box::new(IndependentSampler::new(rng)),
+        SamplerKind::Stratified => Box::new(StratifiedSampler::new(seed, x, y, samples_per_pixel, rng)),
+        SamplerKind::Halton => Box::new(HaltonSampler::new(seed, x, y, rng)),
+        SamplerKind::Sobol => Box::new(SobolSampler::new(seed, x, y, rng)),
+        SamplerKind::BlueNoise => Box::new(BlueNoiseSampler::new(seed, x, y, rng)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::new_rng;
+
+    use super::{Sampler, SamplerKind, new_sampler};
+
+    #[test]
+    fn values_are_in_the_unit_interval() {
+        for kind in SamplerKind::ALL {
+            for (x, y) in [(0, 0), (5, 2), (63, 63), (64, 1000)] {
+                let mut sampler: Box<dyn Sampler> = new_sampler(kind, 1, x, y, 16, new_rng(1, 0));
+                // Past samples_per_pixel and past the dimensions some samplers have tables for
+                for index in 0..40 {
+                    sampler.start_sample(index);
+                    for _ in 0..80 {
+                        let value: f64 = sampler.next_1d();
+                        assert!((0.0..1.0).contains(&value), "{} sampler: {}", kind, value);
+                        let [u, v] = sampler.next_2d();
+                        assert!((0.0..1.0).contains(&u) && (0.0..1.0).contains(&v), "{} sampler: [{}, {}]", kind, u, v);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for kind in SamplerKind::ALL {
+            assert_eq!(kind.to_string().parse::<SamplerKind>(), Ok(kind));
+        }
+        assert!("sobel".parse::<SamplerKind>().is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,118 @@
+use crate::utils::RenderRng;
+
+use super::{sampler::Sampler, hash::{hash, owen_scramble, to_unit}};
+
+// The first two dimensions of the Sobol sequence, Owen scrambled. Every call for
+// a new dimension gets its own scrambling, and the order of the points is
+// shuffled as well so that dimensions don't line up with each other. Following
+// Burley's "Practical Hash-based Owen Scrambling", this needs no tables and has
+// no limit on the number of dimensions. Any power of two number of samples is
+// stratified in both dimensions of each 2D sample.
+pub struct SobolSampler {
+    pixel_seed: u64,
+    index: u32,
+    dimension: u64,
+    rng: RenderRng,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64, x: u32, y: u32, rng: RenderRng) -> Self {
+        Self {
+            pixel_seed: hash(&[seed, x as u64, y as u64]),
+            index: 0,
+            dimension: 0,
+            rng
+        }
+    }
+
+    fn next_seed(&mut self) -> u64 {
+        let seed: u64 = hash(&[self.pixel_seed, self.dimension]);
+        self.dimension += 1;
+        seed
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_sample(&mut self, index: u32) {
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let seed: u64 = self.next_seed();
+        scrambled_sobol_2d(self.index, seed)[0]
+    }
+
+    fn next_2d(&mut self) -> [f64; 2] {
+        let seed: u64 = self.next_seed();
+        scrambled_sobol_2d(self.index, seed)
+    }
+
+    fn rng(&mut self) -> &mut RenderRng {
+        &mut self.rng
+    }
+}
+
+// Point index of the first two Sobol dimensions, shuffled and scrambled by seed
+pub fn scrambled_sobol_2d(index: u32, seed: u64) -> [f64; 2] {
+    let shuffled: u32 = owen_scramble(index, seed as u32);
+    let [x, y] = sobol_2d(shuffled);
+    [
+        to_unit(owen_scramble(x, hash(&[seed, 0]) as u32)),
+        to_unit(owen_scramble(y, hash(&[seed, 1]) as u32))
+    ]
+}
+
+// Point index of the first two Sobol dimensions as 32 bit fractions. The first
+// is the van der Corput sequence, the second has direction numbers v_1 = 1/2 and
+// v_k+1 = v_k xor v_k/2.
+fn sobol_2d(index: u32) -> [u32; 2] {
+    let mut y: u32 = 0;
+    let mut direction: u32 = 1 << 31;
+    let mut bits: u32 = index;
+    while bits != 0 {
+        if bits & 1 == 1 {
+            y ^= direction;
+        }
+        direction ^= direction >> 1;
+        bits >>= 1;
+    }
+
+    [index.reverse_bits(), y]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{samplers::sampler::Sampler, utils::new_rng};
+
+    use super::SobolSampler;
+
+    // The first 2^m points of each 2D sample are a (0, m, 2)-net: every box of
+    // 2^a by 2^(m - a) cells holds exactly one of them
+    #[test]
+    fn points_are_stratified() {
+        for (x, y) in [(0, 0), (3, 7)] {
+            let mut sampler: SobolSampler = SobolSampler::new(1, x, y, new_rng(0, 0));
+            for m in 0..=8 {
+                let count: u32 = 1 << m;
+                for dimension in 0..4 {
+                    let points: Vec<[f64; 2]> = (0..count).map(|index| {
+                        sampler.start_sample(index);
+                        (0..dimension).for_each(|_| { sampler.next_2d(); });
+                        sampler.next_2d()
+                    }).collect();
+
+                    for a in 0..=m {
+                        let (columns, rows) = ((1 << a) as f64, (1 << (m - a)) as f64);
+                        let mut cells: Vec<usize> = points.iter()
+                            .map(|[u, v]| (u * columns) as usize * (rows as usize) + (v * rows) as usize)
+                            .collect();
+                        cells.sort_unstable();
+                        cells.dedup();
+                        assert_eq!(cells.len(), count as usize, "{} points, dimension {}, {} by {} cells", count, dimension, columns, rows);
+                    }
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,111 @@
+use crate::utils::{RenderRng, random_double};
+
+use super::{sampler::Sampler, hash::{hash, permute, random_float, ONE_MINUS_EPSILON}};
+
+// Correlated multi-jittered sampling, from Kensler's paper of the same name.
+// Every dimension of a pixel's samples is split into as many strata as the pixel
+// has samples, each sample falls in its own one and pairs of dimensions are
+// stratified on a grid as well. Samples past samples_per_pixel are independent.
+pub struct StratifiedSampler {
+    pixel_seed: u64,
+    samples: u32,
+    index: u32,
+    dimension: u64,
+    rng: RenderRng,
+}
+
+impl StratifiedSampler {
+    pub fn new(seed: u64, x: u32, y: u32, samples_per_pixel: u32, rng: RenderRng) -> Self {
+        Self {
+            pixel_seed: hash(&[seed, x as u64, y as u64]),
+            samples: samples_per_pixel,
+            index: 0,
+            dimension: 0,
+            rng
+        }
+    }
+
+    // Permutations differ between pixels and dimensions
+    fn next_seed(&mut self) -> u32 {
+        let seed: u32 = hash(&[self.pixel_seed, self.dimension]) as u32;
+        self.dimension += 1;
+        seed
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_sample(&mut self, index: u32) {
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn next_1d(&mut self) -> f64 {
+        let seed: u32 = self.next_seed();
+        if self.index >= self.samples {
+            return random_double(&mut self.rng);
+        }
+
+        let stratum: u32 = permute(self.index, self.samples, seed);
+        let jitter: f64 = random_float(self.index, seed.wrapping_mul(0x68bc_21eb));
+        ((stratum as f64 + jitter) / self.samples as f64).min(ONE_MINUS_EPSILON)
+    }
+
+    // The samples are put on an m by n grid, shuffled so that each of the m columns
+    // and n rows is split in turn into n and m strata that each take one sample.
+    // The grid is as close to square as m * n = samples allows.
+    fn next_2d(&mut self) -> [f64; 2] {
+        let seed: u32 = self.next_seed();
+        if self.index >= self.samples {
+            return [random_double(&mut self.rng), random_double(&mut self.rng)];
+        }
+
+        let m: u32 = (1..=(self.samples as f64).sqrt() as u32).rev().find(|&m| self.samples % m == 0).unwrap_or(1);
+        let n: u32 = self.samples / m;
+        let s: u32 = permute(self.index, self.samples, seed.wrapping_mul(0x5163_3e2d));
+        let sx: u32 = permute(s % m, m, seed.wrapping_mul(0xa511_e9b3));
+        let sy: u32 = permute(s / m, n, seed.wrapping_mul(0x63d8_3595));
+        let jx: f64 = random_float(s, seed.wrapping_mul(0xa399_d265));
+        let jy: f64 = random_float(s, seed.wrapping_mul(0x711a_d6a5));
+
+        [
+            (((s % m) as f64 + (sy as f64 + jx) / n as f64) / m as f64).min(ONE_MINUS_EPSILON),
+            (((s / m) as f64 + (sx as f64 + jy) / m as f64) / n as f64).min(ONE_MINUS_EPSILON)
+        ]
+    }
+
+    fn rng(&mut self) -> &mut RenderRng {
+        &mut self.rng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{samplers::sampler::Sampler, utils::new_rng};
+
+    use super::StratifiedSampler;
+
+    // Each 2D sample has one point in every cell of its m by n grid and in every
+    // one of the samples_per_pixel strata along each axis
+    #[test]
+    fn points_are_stratified() {
+        for (samples, m, n) in [(1, 1, 1), (7, 1, 7), (12, 3, 4), (16, 4, 4), (32, 4, 8)] {
+            let mut sampler: StratifiedSampler = StratifiedSampler::new(1, 3, 7, samples, new_rng(0, 0));
+            let points: Vec<[f64; 2]> = (0..samples).map(|index| {
+                sampler.start_sample(index);
+                sampler.next_1d();
+                sampler.next_2d()
+            }).collect();
+
+            let count = |cell: &dyn Fn(&[f64; 2]) -> usize| -> usize {
+                let mut cells: Vec<usize> = points.iter().map(cell).collect();
+                cells.sort_unstable();
+                cells.dedup();
+                cells.len()
+            };
+            let strata: f64 = samples as f64;
+            assert_eq!(count(&|[u, v]| (u * m as f64) as usize * n + (v * n as f64) as usize), samples as usize);
+            assert_eq!(count(&|[u, _]| (u * strata) as usize), samples as usize);
+            assert_eq!(count(&|[_, v]| (v * strata) as usize), samples as usize);
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,5 @@
+pub mod scene_description;
+pub mod object_builder;
+pub mod scene_error;
+pub mod scene_loader;
+pub mod texture_builder;
\ No newline at end of file
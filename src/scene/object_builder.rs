@@ -0,0 +1,182 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use image::Rgb;
+
+use crate::{
+    mat4::Mat4,
+    hittables::{hittable::Hittable, hittable_list::HittableList, sphere::Sphere, triangle::Triangle, mesh::Mesh, quad::{Quad, make_box}, transformed::{Transformed, Motion}, moving_sphere::MovingSphere, constant_medium::ConstantMedium},
+    materials::{material::Material, lambertian::Lambertian},
+    vec3::{Point3, Vec3}};
+
+use super::{
+    scene_description::{ObjectDescription, TransformDescription, MotionDescription},
+    scene_error::SceneError,
+    scene_loader::relative_to_scene};
+
+// A mesh file loaded with a default material and MTL material overrides, by name
+type MeshKey = (PathBuf, Option<String>, Vec<(String, String)>);
+
+// Builds the objects of a scene. Meshes are cached so that instancing the same
+// mesh several times shares one set of triangles.
+pub struct ObjectBuilder<'a> {
+    file: &'a str,
+    materials: &'a HashMap<String, Material>,
+    meshes: HashMap<MeshKey, Arc<Mesh>>,
+}
+
+impl<'a> ObjectBuilder<'a> {
+    pub fn new(file: &'a str, materials: &'a HashMap<String, Material>) -> Self {
+        Self {
+            file,
+            materials,
+            meshes: HashMap::new()
+        }
+    }
+
+    // field is the path of the object in the scene file, for error messages
+    pub fn build(&mut self, field: &str, description: &ObjectDescription) -> Result<Arc<dyn Hittable + Send + Sync>, SceneError> {
+        Ok(match description {
+            ObjectDescription::Sphere { center, radius, material } => {
+                let material: Material = self.material(format!("{}.sphere.material", field), material)?;
+                Arc::new(Sphere::new(Point3::from(*center), *radius, material))
+            }
+            ObjectDescription::MovingSphere { center0, center1, time0, time1, radius, material } => {
+                if time1 <= time0 {
+                    return Err(self.invalid(format!("{}.moving_sphere.time1", field), format!("must be after time0, got {}", time1)));
+                }
+                let material: Material = self.material(format!("{}.moving_sphere.material", field), material)?;
+                Arc::new(MovingSphere::new(Point3::from(*center0), Point3::from(*center1), *time0, *time1, *radius, material))
+            }
+            ObjectDescription::Quad { corner, u, v, material } => {
+                let material: Material = self.material(format!("{}.quad.material", field), material)?;
+                Arc::new(Quad::new(Point3::from(*corner), Vec3::from(*u), Vec3::from(*v), material))
+            }
+            ObjectDescription::Box { min, max, material } => {
+                let material: Material = self.material(format!("{}.box.material", field), material)?;
+                let mut sides: HittableList = HittableList::new_empty();
+                for side in make_box(Point3::from(*min), Point3::from(*max), material) {
+                    sides.add(Arc::new(side));
+                }
+                Arc::new(sides)
+            }
+            ObjectDescription::Triangle { vertices, normals, uvs, material } => {
+                let material: Material = self.material(format!("{}.triangle.material", field), material)?;
+                let mut triangle: Triangle = Triangle::new(
+                    Point3::from(vertices[0]), Point3::from(vertices[1]), Point3::from(vertices[2]), material);
+                if let Some(normals) = normals {
+                    triangle = triangle.with_normals(normals.map(Vec3::from));
+                }
+                if let Some(uvs) = uvs {
+                    triangle = triangle.with_uvs(uvs.map(|uv| (uv[0], uv[1])));
+                }
+                Arc::new(triangle)
+            }
+            ObjectDescription::Mesh { file: mesh_file, material, material_overrides } => {
+                let mesh_path: PathBuf = relative_to_scene(self.file, mesh_file);
+                let key: MeshKey = (
+                    mesh_path.clone(),
+                    material.clone(),
+                    material_overrides.iter().map(|(mtl_name, material)| (mtl_name.clone(), material.clone())).collect());
+                if let Some(mesh) = self.meshes.get(&key) {
+                    return Ok(Arc::clone(mesh) as Arc<dyn Hittable + Send + Sync>);
+                }
+
+                let default_material: Material = match material {
+                    Some(material) => self.material(format!("{}.mesh.material", field), material)?,
+                    None => Material::new(Arc::new(Lambertian::new(Rgb::from([0.8, 0.8, 0.8])))),
+                };
+                let mut overrides: HashMap<String, Material> = HashMap::new();
+                for (mtl_name, material) in material_overrides {
+                    let override_field: String = format!("{}.mesh.material_overrides.{}", field, mtl_name);
+                    overrides.insert(mtl_name.clone(), self.material(override_field, material)?);
+                }
+
+                let mesh: Mesh = match Mesh::load(&mesh_path, default_material, &overrides) {
+                    Err(ex) => return Err(self.invalid(format!("{}.mesh.file", field), ex.to_string())),
+                    Ok(mesh) => mesh,
+                };
+                if mesh.triangle_count() == 0 {
+                    return Err(self.invalid(format!("{}.mesh.file", field), format!("mesh {} has no faces", mesh_path.display())));
+                }
+
+                let mesh: Arc<Mesh> = Arc::new(mesh);
+                self.meshes.insert(key, Arc::clone(&mesh));
+                mesh
+            }
+            ObjectDescription::ConstantMedium { boundary, density, material } => {
+                if *density <= 0.0 {
+                    return Err(self.invalid(format!("{}.constant_medium.density", field), format!("must be positive, got {}", density)));
+                }
+                if boundary.is_medium() {
+                    return Err(self.invalid(format!("{}.constant_medium.boundary", field), "must be a surface, not another medium".to_string()));
+                }
+                let material: Material = self.material(format!("{}.constant_medium.material", field), material)?;
+                let boundary = self.build(&format!("{}.constant_medium.boundary", field), boundary)?;
+                Arc::new(ConstantMedium::new(boundary, *density, material))
+            }
+            ObjectDescription::Instance { transforms, motion, object } => {
+                let mut transform: Mat4 = Mat4::identity();
+                for (index, step) in transforms.iter().enumerate() {
+                    let step_field: String = format!("{}.instance.transforms[{}]", field, index);
+                    transform = self.transform(step_field, step)? * transform;
+                }
+
+                let motion: Option<Motion> = match motion {
+                    Some(motion) => Some(self.motion(format!("{}.instance.motion", field), motion)?),
+                    None => None,
+                };
+
+                let object = self.build(&format!("{}.instance.object", field), object)?;
+                let transformed: Option<Transformed> = match motion {
+                    Some(motion) => Transformed::new_moving(object, transform, motion),
+                    None => Transformed::new(object, transform),
+                };
+                match transformed {
+                    None => return Err(self.invalid(format!("{}.instance.transforms", field), "transform can't be inverted".to_string())),
+                    Some(transformed) => Arc::new(transformed),
+                }
+            }
+        })
+    }
+
+    fn transform(&self, field: String, description: &TransformDescription) -> Result<Mat4, SceneError> {
+        Ok(match description {
+            TransformDescription::Translate(offset) => Mat4::translation(Vec3::from(*offset)),
+            TransformDescription::Rotate { axis, degrees } => {
+                if Vec3::from(*axis).near_zero() {
+                    return Err(self.invalid(format!("{}.rotate.axis", field), "must not be zero".to_string()));
+                }
+                Mat4::rotation(Vec3::from(*axis), *degrees)
+            }
+            TransformDescription::Scale(factors) => {
+                if factors.contains(&0.0) {
+                    return Err(self.invalid(format!("{}.scale", field), "factors must not be zero".to_string()));
+                }
+                Mat4::scaling(Vec3::from(*factors))
+            }
+        })
+    }
+
+    fn motion(&self, field: String, description: &MotionDescription) -> Result<Motion, SceneError> {
+        if description.time1 <= description.time0 {
+            return Err(self.invalid(format!("{}.time1", field), format!("must be after time0, got {}", description.time1)));
+        }
+
+        Ok(Motion {
+            translate: Vec3::from(description.translate),
+            time0: description.time0,
+            time1: description.time1
+        })
+    }
+
+    fn material(&self, field: String, name: &String) -> Result<Material, SceneError> {
+        match self.materials.get(name) {
+            Some(material) => Ok(material.clone()),
+            None => Err(self.invalid(field, format!("unknown material `{}`", name))),
+        }
+    }
+
+    fn invalid(&self, field: String, message: String) -> SceneError {
+        SceneError::Invalid { file: self.file.to_string(), field, message }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::materials::conductor::ConductorPreset;
+
+// Plain data types mirroring the JSON scene file format.
+// These are converted into renderable types by the scene loader.
+
+pub type ColorDescription = [f64; 3];
+pub type Vec3Description = [f64; 3];
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SceneDescription {
+    #[serde(default)]
+    pub camera: CameraDescription,
+    #[serde(default)]
+    pub background: Option<BackgroundDescription>,
+    #[serde(default)]
+    pub render: RenderDescription,
+    #[serde(default)]
+    pub textures: BTreeMap<String, TextureDescription>,
+    #[serde(default)]
+    pub materials: BTreeMap<String, MaterialDescription>,
+    #[serde(default)]
+    pub objects: Vec<ObjectDescription>,
+    #[serde(default)]
+    pub lights: Vec<LightDescription>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CameraDescription {
+    #[serde(default = "default_look_from")]
+    pub look_from: Vec3Description,
+    #[serde(default = "default_look_at")]
+    pub look_at: Vec3Description,
+    #[serde(default = "default_vup")]
+    pub vup: Vec3Description,
+    // Vertical field of view in degrees
+    #[serde(default = "default_vfov")]
+    pub vfov: f64,
+    #[serde(default)]
+    pub aperture: f64,
+    // Defaults to the distance between look_from and look_at
+    #[serde(default)]
+    pub focus_dist: Option<f64>,
+    // Time interval the shutter is open for, moving objects are blurred over it
+    #[serde(default)]
+    pub shutter_open: f64,
+    #[serde(default)]
+    pub shutter_close: f64,
+}
+
+fn default_look_from() -> Vec3Description { [0.0, 0.0, 0.0] }
+fn default_look_at() -> Vec3Description { [0.0, 0.0, -1.0] }
+fn default_vup() -> Vec3Description { [0.0, 1.0, 0.0] }
+fn default_vfov() -> f64 { 90.0 }
+
+impl Default for CameraDescription {
+    fn default() -> Self {
+        Self {
+            look_from: default_look_from(),
+            look_at: default_look_at(),
+            vup: default_vup(),
+            vfov: default_vfov(),
+            aperture: 0.0,
+            focus_dist: None,
+            shutter_open: 0.0,
+            shutter_close: 0.0
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum BackgroundDescription {
+    Gradient { bottom: ColorDescription, top: ColorDescription },
+    Constant { color: ColorDescription },
+    // Equirectangular image, relative to the scene file, rotated about the Y axis in degrees
+    EnvironmentMap {
+        file: String,
+        #[serde(default)]
+        rotation: f64,
+        #[serde(default = "default_intensity")]
+        intensity: f64,
+    },
+}
+
+fn default_intensity() -> f64 { 1.0 }
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RenderDescription {
+    pub image_width: Option<u32>,
+    pub aspect_ratio: Option<f64>,
+    pub samples_per_pixel: Option<u32>,
+    pub max_depth: Option<u32>,
+    pub gamma: Option<f64>,
+    pub tile_size: Option<u32>,
+    pub seed: Option<u64>,
+}
+
+// Either a plain color or the name of a texture from the scene's textures
+#[derive(Deserialize, Debug)]
+#[serde(untagged, expecting = "expected a color [r, g, b] or the name of a texture")]
+pub enum TextureReference {
+    Color(ColorDescription),
+    Named(String),
+}
+
+// Either a number or the name of a texture, whose channels are averaged
+#[derive(Deserialize, Debug)]
+#[serde(untagged, expecting = "expected a number or the name of a texture")]
+pub enum ScalarReference {
+    Value(f64),
+    Named(String),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum TextureDescription {
+    Solid { color: ColorDescription },
+    // 3D checker pattern of cubes with sides of length scale
+    Checker { scale: f64, even: TextureReference, odd: TextureReference },
+    // Image file mapped over the surface, relative paths are resolved from the scene file's directory
+    Image { file: String },
+    Noise { #[serde(default = "default_noise_scale")] scale: f64 },
+    Turbulence { #[serde(default = "default_noise_scale")] scale: f64 },
+    Marble { #[serde(default = "default_noise_scale")] scale: f64 },
+}
+
+fn default_noise_scale() -> f64 { 1.0 }
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum MaterialDescription {
+    Lambertian { albedo: TextureReference },
+    Metal { albedo: ColorDescription, #[serde(default)] fuzz: f64 },
+    Dielectric { index_of_refraction: f64 },
+    // Microfacet metal, either one of the presets or a complex index of refraction
+    // eta + i k per channel. Roughness goes from 0 (mirror) to 1.
+    Conductor {
+        #[serde(default)]
+        preset: Option<ConductorPreset>,
+        #[serde(default)]
+        eta: Option<ColorDescription>,
+        #[serde(default)]
+        k: Option<ColorDescription>,
+        #[serde(default)]
+        roughness: f64,
+    },
+    // Frosted glass, clear at a roughness of 0
+    RoughDielectric { index_of_refraction: f64, #[serde(default)] roughness: f64 },
+    // Disney style material, the scalar parameters go from 0 to 1
+    Principled {
+        #[serde(default = "default_base_color")]
+        base_color: TextureReference,
+        #[serde(default)]
+        metallic: Option<ScalarReference>,
+        #[serde(default)]
+        roughness: Option<ScalarReference>,
+        #[serde(default)]
+        specular: Option<ScalarReference>,
+        #[serde(default)]
+        clearcoat: Option<ScalarReference>,
+        #[serde(default)]
+        clearcoat_roughness: Option<ScalarReference>,
+        #[serde(default)]
+        sheen: Option<ScalarReference>,
+        #[serde(default)]
+        transmission: Option<ScalarReference>,
+        #[serde(default)]
+        subsurface: Option<ScalarReference>,
+    },
+    DiffuseLight { emit: TextureReference },
+    // Phase functions for the inside of a constant_medium
+    Isotropic { albedo: TextureReference },
+    // g from -1 (back scattering) through 0 (isotropic) to 1 (forward scattering)
+    HenyeyGreenstein { albedo: TextureReference, g: f64 },
+}
+
+fn default_base_color() -> TextureReference { TextureReference::Color([0.8, 0.8, 0.8]) }
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum ObjectDescription {
+    Sphere { center: Vec3Description, radius: f64, material: String },
+    // Sphere moving in a straight line from center0 at time0 to center1 at time1
+    MovingSphere {
+        center0: Vec3Description,
+        center1: Vec3Description,
+        #[serde(default)]
+        time0: f64,
+        #[serde(default = "default_time1")]
+        time1: f64,
+        radius: f64,
+        material: String,
+    },
+    // Parallelogram with a corner and the two edges leaving it
+    Quad { corner: Vec3Description, u: Vec3Description, v: Vec3Description, material: String },
+    // Axis-aligned box between two opposite corners
+    Box { min: Vec3Description, max: Vec3Description, material: String },
+    Triangle {
+        vertices: [Vec3Description; 3],
+        #[serde(default)]
+        normals: Option<[Vec3Description; 3]>,
+        #[serde(default)]
+        uvs: Option<[[f64; 2]; 3]>,
+        material: String,
+    },
+    // Wavefront OBJ file, relative paths are resolved from the scene file's directory
+    Mesh {
+        file: String,
+        // Used for faces without a `usemtl` material
+        #[serde(default)]
+        material: Option<String>,
+        // Replaces MTL materials by name with materials from this scene
+        #[serde(default)]
+        material_overrides: BTreeMap<String, String>,
+    },
+    // Smoke or fog filling a closed boundary object, scattering with a phase function material
+    ConstantMedium { boundary: Box<ObjectDescription>, density: f64, material: String },
+    // Another object moved into place by transforms, applied in the order listed
+    Instance {
+        transforms: Vec<TransformDescription>,
+        // Moves the transformed object over time
+        #[serde(default)]
+        motion: Option<MotionDescription>,
+        object: Box<ObjectDescription>,
+    },
+}
+
+impl ObjectDescription {
+    // Smoke and fog, which the render traces apart from the surfaces
+    pub fn is_medium(&self) -> bool {
+        match self {
+            ObjectDescription::ConstantMedium { .. } => true,
+            ObjectDescription::Instance { object, .. } => object.is_medium(),
+            _ => false,
+        }
+    }
+}
+
+// Straight line movement by translate between time0 and time1. The object stays
+// put before time0 and after time1.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct MotionDescription {
+    pub translate: Vec3Description,
+    #[serde(default)]
+    pub time0: f64,
+    #[serde(default = "default_time1")]
+    pub time1: f64,
+}
+
+fn default_time1() -> f64 { 1.0 }
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum TransformDescription {
+    Translate(Vec3Description),
+    // Counter-clockwise rotation about an axis through the origin
+    Rotate { axis: Vec3Description, degrees: f64 },
+    Scale(Vec3Description),
+}
+
+// Area lights, shorthand for a shape with a diffuse light material
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum LightDescription {
+    Quad { corner: Vec3Description, u: Vec3Description, v: Vec3Description, emission: ColorDescription },
+    Sphere { center: Vec3Description, radius: f64, emission: ColorDescription },
+}
\ No newline at end of file
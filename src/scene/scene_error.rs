@@ -0,0 +1,33 @@
+use std::{fmt::{self, Display}, error::Error, io};
+
+#[derive(Debug)]
+pub enum SceneError {
+    // The scene file could not be read
+    Io { file: String, source: io::Error },
+    // The scene file is not valid JSON or does not match the scene format
+    Parse { file: String, line: usize, column: usize, field: String, message: String },
+    // The scene file parsed but describes something we can't build
+    Invalid { file: String, field: String, message: String },
+}
+
+impl Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io { file, source } =>
+                write!(f, "Error reading scene file {}: {}", file, source),
+            SceneError::Parse { file, line, column, field, message } =>
+                write!(f, "Error parsing scene file {} at line {} column {} (field `{}`): {}", file, line, column, field, message),
+            SceneError::Invalid { file, field, message } =>
+                write!(f, "Invalid scene file {} (field `{}`): {}", file, field, message),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
\ No newline at end of file
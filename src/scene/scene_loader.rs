@@ -0,0 +1,303 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}, sync::Arc};
+
+use image::Rgb;
+
+use crate::{
+    backgrounds::{background::Background, gradient::Gradient, constant::Constant, environment_map::EnvironmentMap},
+    camera::Camera,
+    render_settings::RenderSettings,
+    hittables::{hittable::Hittable, hittable_list::HittableList, sphere::Sphere, quad::Quad},
+    materials::{material::Material, lambertian::Lambertian, metal::Metal, dielectric::Dielectric, diffuse_light::DiffuseLight, isotropic::Isotropic, henyey_greenstein::HenyeyGreenstein,
+        conductor::Conductor, rough_dielectric::RoughDielectric, principled::Principled},
+    vec3::{Point3, Vec3}};
+
+use super::{
+    scene_description::{SceneDescription, CameraDescription, BackgroundDescription, RenderDescription, MaterialDescription, LightDescription},
+    scene_error::SceneError,
+    texture_builder::TextureBuilder,
+    object_builder::ObjectBuilder};
+
+pub struct Scene {
+    pub world: HittableList,
+    // Lights that are sampled directly, they are part of the world as well
+    pub lights: HittableList,
+    // Smoke and fog, kept out of the world so the surfaces inside them can be seen
+    pub media: HittableList,
+    pub camera: CameraDescription,
+    pub background: Arc<dyn Background + Send + Sync>,
+    pub render: RenderDescription,
+}
+
+// The scene rendered when no scene file is given on the command line
+pub fn default_scene() -> Scene {
+    let mut world: HittableList = HittableList::new_empty();
+
+    let material_ground: Material = Material::new(Arc::new(Lambertian::new(Rgb::from([0.8, 0.8, 0.0]))));
+    let material_center: Material = Material::new(Arc::new(Lambertian::new(Rgb::from([0.1, 0.2, 0.5]))));
+    let material_left: Material = Material::new(Arc::new(Dielectric::new(1.5)));
+    let material_right: Material = Material::new(Arc::new(Metal::new(Rgb::from([0.8, 0.6, 0.2]), 0.3)));
+
+    world.add(Arc::new(Sphere::new(Point3::new(0.0, -100.5, -1.0), 100.0, material_ground)));
+    world.add(Arc::new(Sphere::new(Point3::new(0.0, 0.0, -1.0), 0.5, material_center)));
+    world.add(Arc::new(Sphere::new(Point3::new(-1.0, 0.0, -1.0), 0.5, material_left.clone())));
+    world.add(Arc::new(Sphere::new(Point3::new(-1.0, 0.0, -1.0), -0.4, material_left)));
+    world.add(Arc::new(Sphere::new(Point3::new(1.0, 0.0, -1.0), 0.5, material_right)));
+
+    Scene {
+        world,
+        lights: HittableList::new_empty(),
+        media: HittableList::new_empty(),
+        camera: CameraDescription::default(),
+        background: Arc::new(Gradient::default()),
+        render: RenderDescription::default()
+    }
+}
+
+pub fn load_scene(file: &str) -> Result<Scene, SceneError> {
+    let contents: String = match fs::read_to_string(file) {
+        Err(ex) => return Err(SceneError::Io { file: file.to_string(), source: ex }),
+        Ok(contents) => contents,
+    };
+
+    let description: SceneDescription = parse_scene(file, &contents)?;
+    build_scene(file, description)
+}
+
+pub fn parse_scene(file: &str, contents: &str) -> Result<SceneDescription, SceneError> {
+    let deserializer = &mut serde_json::Deserializer::from_str(contents);
+
+    match serde_path_to_error::deserialize(deserializer) {
+        Ok(description) => Ok(description),
+        Err(ex) => {
+            let field: String = ex.path().to_string();
+            let inner: &serde_json::Error = ex.inner();
+            // serde_json appends the position to its message, we report it separately
+            let full_message: String = inner.to_string();
+            let message: &str = match full_message.rsplit_once(" at line ") {
+                Some((message, _)) => message,
+                None => &full_message,
+            };
+
+            Err(SceneError::Parse {
+                file: file.to_string(),
+                line: inner.line(),
+                column: inner.column(),
+                field,
+                message: message.to_string()
+            })
+        }
+    }
+}
+
+fn build_scene(file: &str, description: SceneDescription) -> Result<Scene, SceneError> {
+    let invalid = |field: String, message: String| SceneError::Invalid { file: file.to_string(), field, message };
+
+    let mut textures: TextureBuilder = TextureBuilder::new(file, &description.textures);
+    let mut materials: HashMap<String, Material> = HashMap::new();
+    for (name, material) in &description.materials {
+        materials.insert(name.clone(), build_material(&format!("materials.{}", name), material, &mut textures)?);
+    }
+
+    let mut objects: ObjectBuilder = ObjectBuilder::new(file, &materials);
+    let mut world: HittableList = HittableList::new_empty();
+    let mut media: HittableList = HittableList::new_empty();
+    for (index, object) in description.objects.iter().enumerate() {
+        let built: Arc<dyn Hittable + Send + Sync> = objects.build(&format!("objects[{}]", index), object)?;
+        if object.is_medium() {
+            media.add(built);
+        } else {
+            world.add(built);
+        }
+    }
+
+    let mut lights: HittableList = HittableList::new_empty();
+    for light in &description.lights {
+        let light: Arc<dyn Hittable + Send + Sync> = match light {
+            LightDescription::Quad { corner, u, v, emission } => {
+                let material: Material = Material::new(Arc::new(DiffuseLight::new(to_rgb(emission))));
+                Arc::new(Quad::new(Point3::from(*corner), Vec3::from(*u), Vec3::from(*v), material))
+            }
+            LightDescription::Sphere { center, radius, emission } => {
+                let material: Material = Material::new(Arc::new(DiffuseLight::new(to_rgb(emission))));
+                Arc::new(Sphere::new(Point3::from(*center), *radius, material))
+            }
+        };
+        world.add(Arc::clone(&light));
+        lights.add(light);
+    }
+
+    let background: Arc<dyn Background + Send + Sync> = match &description.background {
+        None => Arc::new(Gradient::default()),
+        Some(BackgroundDescription::Gradient { bottom, top }) => Arc::new(Gradient::new(to_rgb(bottom), to_rgb(top))),
+        Some(BackgroundDescription::Constant { color }) => Arc::new(Constant::new(to_rgb(color))),
+        Some(BackgroundDescription::EnvironmentMap { file: map_file, rotation, intensity }) => {
+            if *intensity < 0.0 {
+                return Err(invalid("background.environment_map.intensity".to_string(), format!("must not be negative, got {}", intensity)));
+            }
+            let path: PathBuf = relative_to_scene(file, map_file);
+            match EnvironmentMap::load(&path, *rotation, *intensity) {
+                Err(ex) => return Err(invalid("background.environment_map.file".to_string(), format!("could not load {}: {}", path.display(), ex))),
+                Ok(map) => Arc::new(map),
+            }
+        }
+    };
+
+    if let Err((field, message)) = validate_camera(&description.camera) {
+        return Err(invalid(field, message));
+    }
+
+    let mut settings: RenderSettings = RenderSettings::default();
+    apply_render_description(&mut settings, &description.render);
+    if let Err(ex) = settings.validate() {
+        return Err(invalid(format!("render.{}", ex.setting), ex.message));
+    }
+
+    Ok(Scene {
+        world,
+        lights,
+        media,
+        camera: description.camera,
+        background,
+        render: description.render
+    })
+}
+
+// Returns the name of the offending field and what is wrong with it
+pub fn validate_camera(camera: &CameraDescription) -> Result<(), (String, String)> {
+    if Point3::from(camera.look_from) == Point3::from(camera.look_at) {
+        return Err(("camera.look_at".to_string(), "must be different from camera.look_from".to_string()));
+    }
+    if Vec3::from(camera.vup).cross(Point3::from(camera.look_from) - Point3::from(camera.look_at)).near_zero() {
+        return Err(("camera.vup".to_string(), "must not be parallel to the viewing direction".to_string()));
+    }
+    if camera.vfov <= 0.0 || camera.vfov >= 180.0 {
+        return Err(("camera.vfov".to_string(), format!("must be between 0 and 180 degrees, got {}", camera.vfov)));
+    }
+    if camera.aperture < 0.0 {
+        return Err(("camera.aperture".to_string(), format!("must not be negative, got {}", camera.aperture)));
+    }
+    if camera.shutter_close < camera.shutter_open {
+        return Err(("camera.shutter_close".to_string(), format!("must not be before camera.shutter_open, got {}", camera.shutter_close)));
+    }
+    if let Some(focus_dist) = camera.focus_dist {
+        if focus_dist <= 0.0 {
+            return Err(("camera.focus_dist".to_string(), format!("must be positive, got {}", focus_dist)));
+        }
+    }
+
+    Ok(())
+}
+
+// Overrides the settings with the ones given in the scene file
+pub fn apply_render_description(settings: &mut RenderSettings, render: &RenderDescription) {
+    if let Some(image_width) = render.image_width { settings.image_width = image_width; }
+    if let Some(aspect_ratio) = render.aspect_ratio { settings.aspect_ratio = aspect_ratio; }
+    if let Some(samples_per_pixel) = render.samples_per_pixel { settings.samples_per_pixel = samples_per_pixel; }
+    if let Some(seed) = render.seed { settings.seed = seed; }
+    if let Some(max_depth) = render.max_depth { settings.max_depth = max_depth; }
+    if let Some(gamma) = render.gamma { settings.gamma = gamma; }
+    if let Some(tile_size) = render.tile_size { settings.tile_size = tile_size; }
+}
+
+pub fn build_camera(camera: &CameraDescription, aspect_ratio: f64) -> Camera {
+    let look_from: Point3 = Point3::from(camera.look_from);
+    let look_at: Point3 = Point3::from(camera.look_at);
+    let focus_dist: f64 = camera.focus_dist.unwrap_or_else(|| (look_from - look_at).length());
+
+    Camera::new(look_from, look_at, Vec3::from(camera.vup), camera.vfov, aspect_ratio, camera.aperture, focus_dist,
+        camera.shutter_open, camera.shutter_close)
+}
+
+fn build_material(field: &str, description: &MaterialDescription, textures: &mut TextureBuilder) -> Result<Material, SceneError> {
+    Ok(match description {
+        MaterialDescription::Lambertian { albedo } => {
+            let albedo = textures.resolve(format!("{}.lambertian.albedo", field), albedo)?;
+            Material::new(Arc::new(Lambertian::new_textured(albedo)))
+        }
+        MaterialDescription::Metal { albedo, fuzz } =>
+            Material::new(Arc::new(Metal::new(to_rgb(albedo), *fuzz))),
+        MaterialDescription::Dielectric { index_of_refraction } =>
+            Material::new(Arc::new(Dielectric::new(*index_of_refraction))),
+        MaterialDescription::Conductor { preset, eta, k, roughness } => {
+            check_roughness(textures.file(), &format!("{}.conductor.roughness", field), *roughness)?;
+            let conductor: Conductor = match (preset, eta, k) {
+                (Some(preset), None, None) => Conductor::from_preset(*preset, *roughness),
+                (None, Some(eta), Some(k)) => Conductor::new(*eta, *k, *roughness),
+                _ => return Err(SceneError::Invalid {
+                    file: textures.file().to_string(),
+                    field: format!("{}.conductor", field),
+                    message: "needs either a preset or both eta and k".to_string()
+                }),
+            };
+            Material::new(Arc::new(conductor))
+        }
+        MaterialDescription::Principled {
+            base_color, metallic, roughness, specular, clearcoat, clearcoat_roughness, sheen, transmission, subsurface
+        } => {
+            let base_color = textures.resolve(format!("{}.principled.base_color", field), base_color)?;
+            let mut principled: Principled = Principled::new(base_color);
+            let parameters = [
+                (&mut principled.metallic, metallic, "metallic"),
+                (&mut principled.roughness, roughness, "roughness"),
+                (&mut principled.specular, specular, "specular"),
+                (&mut principled.clearcoat, clearcoat, "clearcoat"),
+                (&mut principled.clearcoat_roughness, clearcoat_roughness, "clearcoat_roughness"),
+                (&mut principled.sheen, sheen, "sheen"),
+                (&mut principled.transmission, transmission, "transmission"),
+                (&mut principled.subsurface, subsurface, "subsurface")
+            ];
+            for (parameter, reference, name) in parameters {
+                if let Some(reference) = reference {
+                    *parameter = textures.resolve_scalar(format!("{}.principled.{}", field, name), reference)?;
+                }
+            }
+            Material::new(Arc::new(principled))
+        }
+        MaterialDescription::RoughDielectric { index_of_refraction, roughness } => {
+            check_roughness(textures.file(), &format!("{}.rough_dielectric.roughness", field), *roughness)?;
+            Material::new(Arc::new(RoughDielectric::new(*index_of_refraction, *roughness)))
+        }
+        MaterialDescription::DiffuseLight { emit } => {
+            let emit = textures.resolve(format!("{}.diffuse_light.emit", field), emit)?;
+            Material::new(Arc::new(DiffuseLight::new_textured(emit)))
+        }
+        MaterialDescription::Isotropic { albedo } => {
+            let albedo = textures.resolve(format!("{}.isotropic.albedo", field), albedo)?;
+            Material::new(Arc::new(Isotropic::new_textured(albedo)))
+        }
+        MaterialDescription::HenyeyGreenstein { albedo, g } => {
+            if *g <= -1.0 || *g >= 1.0 {
+                return Err(SceneError::Invalid {
+                    file: textures.file().to_string(),
+                    field: format!("{}.henyey_greenstein.g", field),
+                    message: format!("must be between -1 and 1, got {}", g)
+                });
+            }
+            let albedo = textures.resolve(format!("{}.henyey_greenstein.albedo", field), albedo)?;
+            Material::new(Arc::new(HenyeyGreenstein::new_textured(albedo, *g)))
+        }
+    })
+}
+
+fn check_roughness(file: &str, field: &str, roughness: f64) -> Result<(), SceneError> {
+    if !(0.0..=1.0).contains(&roughness) {
+        return Err(SceneError::Invalid {
+            file: file.to_string(),
+            field: field.to_string(),
+            message: format!("must be between 0 and 1, got {}", roughness)
+        });
+    }
+    Ok(())
+}
+
+// Paths in a scene file are relative to the directory the scene file is in
+pub fn relative_to_scene(scene_file: &str, path: &str) -> PathBuf {
+    match Path::new(scene_file).parent() {
+        Some(directory) => directory.join(path),
+        None => PathBuf::from(path),
+    }
+}
+
+fn to_rgb(color: &[f64; 3]) -> Rgb<f64> {
+    Rgb::from(*color)
+}
\ No newline at end of file
@@ -0,0 +1,117 @@
+use std::{collections::{BTreeMap, HashMap, HashSet}, path::Path, sync::Arc};
+
+use image::Rgb;
+
+use crate::{
+    textures::{
+        texture::Texture,
+        solid_color::SolidColor,
+        checker::CheckerTexture,
+        image_texture::ImageTexture,
+        noise_texture::{NoiseTexture, NoiseKind}},
+    utils::{RenderRng, new_rng}};
+
+use super::{
+    scene_description::{TextureDescription, TextureReference, ScalarReference},
+    scene_error::SceneError};
+
+// Seed of the noise textures' random lattices, which are part of the scene rather
+// than of the render, so they don't change with the render seed
+const NOISE_SEED: u64 = 0;
+
+// Builds the named textures of a scene on demand, so textures can refer to each
+// other in any order. Each texture is only built once and shared between users.
+pub struct TextureBuilder<'a> {
+    file: &'a str,
+    descriptions: &'a BTreeMap<String, TextureDescription>,
+    built: HashMap<String, Arc<dyn Texture + Send + Sync>>,
+    in_progress: HashSet<String>,
+}
+
+impl<'a> TextureBuilder<'a> {
+    pub fn new(file: &'a str, descriptions: &'a BTreeMap<String, TextureDescription>) -> Self {
+        Self {
+            file,
+            descriptions,
+            built: HashMap::new(),
+            in_progress: HashSet::new()
+        }
+    }
+
+    // Scene file the textures come from
+    pub fn file(&self) -> &'a str {
+        self.file
+    }
+
+    // field is where the reference was made, for error messages
+    pub fn resolve(&mut self, field: String, reference: &TextureReference) -> Result<Arc<dyn Texture + Send + Sync>, SceneError> {
+        match reference {
+            TextureReference::Color(color) => Ok(Arc::new(SolidColor::new(Rgb::from(*color)))),
+            TextureReference::Named(name) => self.named(field, name),
+        }
+    }
+
+    // Scalar parameters between 0 and 1, as a grey texture
+    pub fn resolve_scalar(&mut self, field: String, reference: &ScalarReference) -> Result<Arc<dyn Texture + Send + Sync>, SceneError> {
+        match reference {
+            ScalarReference::Value(value) => {
+                if !(0.0..=1.0).contains(value) {
+                    return Err(self.invalid(field, format!("must be between 0 and 1, got {}", value)));
+                }
+                Ok(Arc::new(SolidColor::new(Rgb::from([*value, *value, *value]))))
+            }
+            ScalarReference::Named(name) => self.named(field, name),
+        }
+    }
+
+    fn named(&mut self, field: String, name: &String) -> Result<Arc<dyn Texture + Send + Sync>, SceneError> {
+        if let Some(texture) = self.built.get(name) {
+            return Ok(Arc::clone(texture));
+        }
+
+        let descriptions: &'a BTreeMap<String, TextureDescription> = self.descriptions;
+        let description: &TextureDescription = match descriptions.get(name) {
+            None => return Err(self.invalid(field, format!("unknown texture `{}`", name))),
+            Some(description) => description,
+        };
+        if !self.in_progress.insert(name.clone()) {
+            return Err(self.invalid(field, format!("texture `{}` refers back to itself", name)));
+        }
+
+        let texture_field: String = format!("textures.{}", name);
+        // Every noise texture gets its own lattice, whatever order they are built in
+        let mut noise_rng: RenderRng = new_rng(NOISE_SEED, descriptions.keys().position(|key| key == name).unwrap_or(0) as u64);
+        let texture: Arc<dyn Texture + Send + Sync> = match description {
+            TextureDescription::Solid { color } => Arc::new(SolidColor::new(Rgb::from(*color))),
+            TextureDescription::Checker { scale, even, odd } => {
+                if *scale <= 0.0 {
+                    return Err(self.invalid(format!("{}.checker.scale", texture_field), format!("must be positive, got {}", scale)));
+                }
+                let even = self.resolve(format!("{}.checker.even", texture_field), even)?;
+                let odd = self.resolve(format!("{}.checker.odd", texture_field), odd)?;
+                Arc::new(CheckerTexture::new(*scale, even, odd))
+            }
+            TextureDescription::Image { file: image_file } => {
+                let path = match Path::new(self.file).parent() {
+                    Some(directory) => directory.join(image_file),
+                    None => Path::new(image_file).to_path_buf(),
+                };
+                match ImageTexture::load(&path) {
+                    Err(ex) => return Err(self.invalid(format!("{}.image.file", texture_field), format!("could not load {}: {}", path.display(), ex))),
+                    Ok(texture) => Arc::new(texture),
+                }
+            }
+            TextureDescription::Noise { scale } => Arc::new(NoiseTexture::new(&mut noise_rng, *scale, NoiseKind::Smooth)),
+            TextureDescription::Turbulence { scale } => Arc::new(NoiseTexture::new(&mut noise_rng, *scale, NoiseKind::Turbulence)),
+            TextureDescription::Marble { scale } => Arc::new(NoiseTexture::new(&mut noise_rng, *scale, NoiseKind::Marble)),
+        };
+
+        self.in_progress.remove(name);
+        self.built.insert(name.clone(), Arc::clone(&texture));
+        Ok(texture)
+    }
+
+    fn invalid(&self, field: String, message: String) -> SceneError {
+        SceneError::Invalid { file: self.file.to_string(), field, message }
+    }
+}
\ No newline at end of file
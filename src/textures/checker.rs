@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use crate::rgb_wrapper::RgbWrapper;
+use crate::vec3::Point3;
+
+use super::texture::Texture;
+
+// 3D checker pattern of cubes with sides of length scale, alternating between two textures
+#[derive(Clone)]
+pub struct CheckerTexture {
+    inv_scale: f64,
+    pub even: Arc<dyn Texture + Send + Sync>,
+    pub odd: Arc<dyn Texture + Send + Sync>,
+}
+
+impl CheckerTexture {
+    pub fn new(scale: f64, even: Arc<dyn Texture + Send + Sync>, odd: Arc<dyn Texture + Send + Sync>) -> Self {
+        Self {
+            inv_scale: 1.0 / scale,
+            even,
+            odd
+        }
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> RgbWrapper {
+        let x: i64 = (self.inv_scale * p.x()).floor() as i64;
+        let y: i64 = (self.inv_scale * p.y()).floor() as i64;
+        let z: i64 = (self.inv_scale * p.z()).floor() as i64;
+
+        if (x + y + z) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use image::Rgb;
+
+    use crate::{textures::{solid_color::SolidColor, texture::Texture}, vec3::Point3};
+
+    use super::CheckerTexture;
+
+    fn is_even(checker: &CheckerTexture, p: Point3) -> bool {
+        checker.value(0.0, 0.0, &p).0 == Rgb::from([1.0, 1.0, 1.0])
+    }
+
+    #[test]
+    fn neighbouring_cubes_alternate_across_zero() {
+        let white: Arc<SolidColor> = Arc::new(SolidColor::new(Rgb::from([1.0, 1.0, 1.0])));
+        let black: Arc<SolidColor> = Arc::new(SolidColor::new(Rgb::from([0.0, 0.0, 0.0])));
+        let checker: CheckerTexture = CheckerTexture::new(2.0, white, black);
+
+        // Centers of the cubes from -3 to 2 along each axis
+        let center = |i: i32| (i as f64 + 0.5) * 2.0;
+        for i in -3..3 {
+            for j in -3..3 {
+                for k in -3..3 {
+                    let p: Point3 = Point3::new(center(i), center(j), center(k));
+                    assert_eq!(is_even(&checker, p), (i + j + k).rem_euclid(2) == 0, "cube ({}, {}, {})", i, j, k);
+                }
+            }
+        }
+        // The cube from -2 to 0 along x is odd, it starts right at its edge
+        assert!(!is_even(&checker, Point3::new(-2.0, 0.5, 0.5)));
+        assert!(is_even(&checker, Point3::new(0.0, 0.5, 0.5)));
+    }
+}
\ No newline at end of file
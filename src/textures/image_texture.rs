@@ -0,0 +1,111 @@
+use std::path::Path;
+
+use image::{Rgb, RgbImage, ImageResult};
+
+use crate::rgb_wrapper::RgbWrapper;
+use crate::utils::clamp;
+use crate::vec3::Point3;
+
+use super::texture::Texture;
+
+// Texture read from an image file, mapped over the surface (u, v) coordinates
+#[derive(Clone)]
+pub struct ImageTexture {
+    image: RgbImage,
+}
+
+impl ImageTexture {
+    pub fn load(file: &Path) -> ImageResult<Self> {
+        Ok(Self {
+            image: image::open(file)?.to_rgb8()
+        })
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: &Point3) -> RgbWrapper {
+        // Images start from the top left, so flip v
+        let u: f64 = clamp(u, 0.0, 1.0);
+        let v: f64 = 1.0 - clamp(v, 0.0, 1.0);
+
+        let i: u32 = ((u * self.image.width() as f64) as u32).min(self.image.width() - 1);
+        let j: u32 = ((v * self.image.height() as f64) as u32).min(self.image.height() - 1);
+        let pixel: &Rgb<u8> = self.image.get_pixel(i, j);
+
+        RgbWrapper(Rgb::from([
+            srgb_to_linear(pixel[0]),
+            srgb_to_linear(pixel[1]),
+            srgb_to_linear(pixel[2])
+        ]))
+    }
+}
+
+// Image files store gamma encoded values, lighting needs them linear
+pub fn srgb_to_linear(component: u8) -> f64 {
+    let c: f64 = component as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgb, RgbImage};
+
+    use crate::{textures::texture::Texture, vec3::Point3};
+
+    use super::{ImageTexture, srgb_to_linear};
+
+    // Red and green along the top row, blue and white along the bottom one
+    fn texture() -> ImageTexture {
+        let mut image: RgbImage = RgbImage::new(2, 2);
+        image.put_pixel(0, 0, Rgb::from([255, 0, 0]));
+        image.put_pixel(1, 0, Rgb::from([0, 255, 0]));
+        image.put_pixel(0, 1, Rgb::from([0, 0, 255]));
+        image.put_pixel(1, 1, Rgb::from([255, 255, 255]));
+        ImageTexture { image }
+    }
+
+    fn value(texture: &ImageTexture, u: f64, v: f64) -> [f64; 3] {
+        texture.value(u, v, &Point3::new(0.0, 0.0, 0.0)).0.0
+    }
+
+    #[test]
+    fn srgb_endpoints_map_to_zero_and_one() {
+        assert_eq!(srgb_to_linear(0), 0.0);
+        assert!((srgb_to_linear(255) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn srgb_is_linear_below_the_breakpoint() {
+        // 10 / 255 is just below 0.04045 and 11 / 255 just above it
+        assert_eq!(srgb_to_linear(10), 10.0 / 255.0 / 12.92);
+        assert_eq!(srgb_to_linear(11), ((11.0 / 255.0 + 0.055) / 1.055f64).powf(2.4));
+        // The two pieces meet without a jump
+        let at_breakpoint: f64 = 0.04045 / 12.92;
+        assert!((((0.04045 + 0.055) / 1.055f64).powf(2.4) - at_breakpoint).abs() < 1e-7);
+        for component in 0..255 {
+            assert!(srgb_to_linear(component) < srgb_to_linear(component + 1));
+        }
+    }
+
+    #[test]
+    fn v_goes_up_the_image() {
+        let texture: ImageTexture = texture();
+        assert_eq!(value(&texture, 0.25, 0.75), [1.0, 0.0, 0.0]);
+        assert_eq!(value(&texture, 0.75, 0.75), [0.0, 1.0, 0.0]);
+        assert_eq!(value(&texture, 0.25, 0.25), [0.0, 0.0, 1.0]);
+        assert_eq!(value(&texture, 0.75, 0.25), [1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn coordinates_outside_the_image_are_clamped_to_its_edges() {
+        let texture: ImageTexture = texture();
+        assert_eq!(value(&texture, 0.0, 1.0), [1.0, 0.0, 0.0]);
+        assert_eq!(value(&texture, 1.0, 0.0), [1.0, 1.0, 1.0]);
+        assert_eq!(value(&texture, -0.5, 2.0), [1.0, 0.0, 0.0]);
+        assert_eq!(value(&texture, 1.5, -1.0), [1.0, 1.0, 1.0]);
+    }
+}
\ No newline at end of file
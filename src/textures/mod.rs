@@ -0,0 +1,6 @@
+pub mod texture;
+pub mod solid_color;
+pub mod checker;
+pub mod image_texture;
+pub mod perlin;
+pub mod noise_texture;
\ No newline at end of file
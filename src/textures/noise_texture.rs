@@ -0,0 +1,50 @@
+use image::Rgb;
+
+use crate::rgb_wrapper::RgbWrapper;
+use crate::vec3::{Point3, F64Multiplier};
+use crate::utils::RenderRng;
+
+use super::{texture::Texture, perlin::Perlin};
+
+const TURBULENCE_DEPTH: u32 = 7;
+
+#[derive(Clone, Copy)]
+pub enum NoiseKind {
+    // Plain smooth Perlin noise
+    Smooth,
+    // Several octaves of noise summed together
+    Turbulence,
+    // Sine stripes along z whose phase is disturbed by turbulence
+    Marble,
+}
+
+// Grey scale procedural texture built from Perlin noise
+#[derive(Clone)]
+pub struct NoiseTexture {
+    noise: Perlin,
+    pub scale: f64,
+    pub kind: NoiseKind,
+}
+
+impl NoiseTexture {
+    pub fn new(rng: &mut RenderRng, scale: f64, kind: NoiseKind) -> Self {
+        Self {
+            noise: Perlin::new(rng),
+            scale,
+            kind
+        }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: &Point3) -> RgbWrapper {
+        let scaled: Point3 = F64Multiplier(self.scale) * *p;
+        let intensity: f64 = match self.kind {
+            NoiseKind::Smooth => 0.5 * (1.0 + self.noise.noise(&scaled)),
+            NoiseKind::Turbulence => self.noise.turb(&scaled, TURBULENCE_DEPTH),
+            NoiseKind::Marble => 0.5 * (1.0 + (scaled.z() + 10.0*self.noise.turb(p, TURBULENCE_DEPTH)).sin()),
+        };
+
+        RgbWrapper(Rgb::from([intensity, intensity, intensity]))
+    }
+}
\ No newline at end of file
@@ -0,0 +1,100 @@
+use crate::utils::{RenderRng, random_double};
+use crate::vec3::{Point3, Vec3, F64Multiplier};
+
+const POINT_COUNT: usize = 256;
+
+// Perlin gradient noise with random unit vectors at the lattice points
+#[derive(Clone)]
+pub struct Perlin {
+    ranvec: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(rng: &mut RenderRng) -> Self {
+        Self {
+            ranvec: (0..POINT_COUNT).map(|_| Vec3::new_random_in_range(rng, -1.0, 1.0).unit_vector()).collect(),
+            perm_x: Self::generate_perm(rng),
+            perm_y: Self::generate_perm(rng),
+            perm_z: Self::generate_perm(rng)
+        }
+    }
+
+    // Smooth noise roughly in the range [-1, 1]
+    pub fn noise(&self, p: &Point3) -> f64 {
+        let u: f64 = p.x() - p.x().floor();
+        let v: f64 = p.y() - p.y().floor();
+        let w: f64 = p.z() - p.z().floor();
+
+        let i: i64 = p.x().floor() as i64;
+        let j: i64 = p.y().floor() as i64;
+        let k: i64 = p.z().floor() as i64;
+
+        let mut c: [[[Vec3; 2]; 2]; 2] = [[[Vec3::new_empty(); 2]; 2]; 2];
+        for (di, plane) in c.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    *corner = self.ranvec[
+                        self.perm_x[((i + di as i64) & 255) as usize]
+                        ^ self.perm_y[((j + dj as i64) & 255) as usize]
+                        ^ self.perm_z[((k + dk as i64) & 255) as usize]
+                    ];
+                }
+            }
+        }
+
+        Self::perlin_interp(&c, u, v, w)
+    }
+
+    // Sum of several octaves of noise, used for turbulent patterns
+    pub fn turb(&self, p: &Point3, depth: u32) -> f64 {
+        let mut accum: f64 = 0.0;
+        let mut temp_p: Point3 = *p;
+        let mut weight: f64 = 1.0;
+
+        for _ in 0..depth {
+            accum += weight * self.noise(&temp_p);
+            weight *= 0.5;
+            temp_p = F64Multiplier(2.0) * temp_p;
+        }
+
+        accum.abs()
+    }
+
+    fn generate_perm(rng: &mut RenderRng) -> Vec<usize> {
+        let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+
+        // Fisher-Yates shuffle
+        for i in (1..POINT_COUNT).rev() {
+            let target: usize = (random_double(rng) * (i + 1) as f64) as usize;
+            p.swap(i, target.min(i));
+        }
+
+        p
+    }
+
+    fn perlin_interp(c: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+        // Hermite smoothing to avoid grid artifacts
+        let uu: f64 = u*u*(3.0 - 2.0*u);
+        let vv: f64 = v*v*(3.0 - 2.0*v);
+        let ww: f64 = w*w*(3.0 - 2.0*w);
+        let mut accum: f64 = 0.0;
+
+        for (i, plane) in c.iter().enumerate() {
+            for (j, row) in plane.iter().enumerate() {
+                for (k, corner) in row.iter().enumerate() {
+                    let (fi, fj, fk) = (i as f64, j as f64, k as f64);
+                    let weight_v: Vec3 = Vec3::new(u - fi, v - fj, w - fk);
+                    accum += (fi*uu + (1.0 - fi)*(1.0 - uu))
+                        * (fj*vv + (1.0 - fj)*(1.0 - vv))
+                        * (fk*ww + (1.0 - fk)*(1.0 - ww))
+                        * corner.dot(weight_v);
+                }
+            }
+        }
+
+        accum
+    }
+}
\ No newline at end of file
@@ -0,0 +1,25 @@
+use image::Rgb;
+
+use crate::rgb_wrapper::RgbWrapper;
+use crate::vec3::Point3;
+
+use super::texture::Texture;
+
+#[derive(Clone, Copy)]
+pub struct SolidColor {
+    pub color: RgbWrapper,
+}
+
+impl SolidColor {
+    pub fn new(color: Rgb<f64>) -> Self {
+        Self {
+            color: RgbWrapper(color)
+        }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: &Point3) -> RgbWrapper {
+        self.color
+    }
+}
\ No newline at end of file
@@ -0,0 +1,8 @@
+use crate::rgb_wrapper::RgbWrapper;
+use crate::vec3::Point3;
+
+// Color that varies over a surface, looked up by the surface (u, v) coordinates
+// of the hit and/or its position in space
+pub trait Texture {
+    fn value(&self, u: f64, v: f64, p: &Point3) -> RgbWrapper;
+}
\ No newline at end of file
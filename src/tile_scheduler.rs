@@ -0,0 +1,120 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Rectangle of pixels in image buffer coordinates, the end bounds are exclusive
+#[derive(Clone, Copy, Debug)]
+pub struct Tile {
+    pub x_start: u32,
+    pub x_end: u32,
+    pub y_start: u32,
+    pub y_end: u32,
+}
+
+impl Tile {
+    pub fn width(&self) -> u32 {
+        self.x_end - self.x_start
+    }
+
+    pub fn height(&self) -> u32 {
+        self.y_end - self.y_start
+    }
+
+    pub fn pixel_count(&self) -> usize {
+        (self.width() * self.height()) as usize
+    }
+}
+
+// Shared queue of tiles that worker threads pull from until it is empty. Workers
+// that land on cheap tiles simply come back for more instead of sitting idle.
+pub struct TileQueue {
+    tiles: Vec<Tile>,
+    next: AtomicUsize,
+}
+
+impl TileQueue {
+    pub fn new(image_width: u32, image_height: u32, tile_size: u32) -> Self {
+        Self {
+            tiles: split_into_tiles(image_width, image_height, tile_size),
+            next: AtomicUsize::new(0)
+        }
+    }
+
+    pub fn next_tile(&self) -> Option<Tile> {
+        let index: usize = self.next.fetch_add(1, Ordering::Relaxed);
+        self.tiles.get(index).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+}
+
+pub fn split_into_tiles(image_width: u32, image_height: u32, tile_size: u32) -> Vec<Tile> {
+    let mut tiles: Vec<Tile> = Vec::new();
+
+    for y_start in (0..image_height).step_by(tile_size as usize) {
+        let y_end: u32 = (y_start + tile_size).min(image_height);
+        for x_start in (0..image_width).step_by(tile_size as usize) {
+            let x_end: u32 = (x_start + tile_size).min(image_width);
+            tiles.push(Tile { x_start, x_end, y_start, y_end });
+        }
+    }
+
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{Tile, TileQueue, split_into_tiles};
+
+    const WIDTH: u32 = 100;
+    const HEIGHT: u32 = 70;
+    const TILE_SIZE: u32 = 16;
+
+    #[test]
+    fn every_tile_is_handed_out_once_across_threads() {
+        let queue: TileQueue = TileQueue::new(WIDTH, HEIGHT, TILE_SIZE);
+        let handed_out: Vec<Vec<Tile>> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..8).map(|_| scope.spawn(|| {
+                let mut tiles: Vec<Tile> = Vec::new();
+                while let Some(tile) = queue.next_tile() {
+                    tiles.push(tile);
+                }
+                tiles
+            })).collect();
+            workers.into_iter().map(|worker| worker.join().unwrap()).collect()
+        });
+
+        // Between them the workers cover every pixel exactly once
+        let mut covered: Vec<u32> = vec![0; (WIDTH*HEIGHT) as usize];
+        for tile in handed_out.iter().flatten() {
+            for y in tile.y_start..tile.y_end {
+                for x in tile.x_start..tile.x_end {
+                    covered[(y*WIDTH + x) as usize] += 1;
+                }
+            }
+        }
+        assert_eq!(handed_out.iter().map(|tiles| tiles.len()).sum::<usize>(), queue.len());
+        assert!(covered.iter().all(|&count| count == 1));
+        assert!(queue.next_tile().is_none());
+    }
+
+    #[test]
+    fn edge_tiles_are_clipped_to_the_image() {
+        let tiles: Vec<Tile> = split_into_tiles(WIDTH, HEIGHT, TILE_SIZE);
+        assert_eq!(tiles.len(), 7*5);
+
+        for tile in &tiles {
+            assert!(tile.x_end <= WIDTH && tile.y_end <= HEIGHT, "{:?}", tile);
+            let width: u32 = if tile.x_start == 96 { 4 } else { TILE_SIZE };
+            let height: u32 = if tile.y_start == 64 { 6 } else { TILE_SIZE };
+            assert_eq!((tile.width(), tile.height()), (width, height), "{:?}", tile);
+            assert_eq!(tile.pixel_count(), (width*height) as usize);
+        }
+    }
+}
\ No newline at end of file
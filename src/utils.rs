@@ -1,16 +1,30 @@
-use rand::distributions::{Uniform, Distribution};
+use rand::{SeedableRng, distributions::{Uniform, Distribution}};
+use rand_chacha::ChaCha8Rng;
 
-pub fn random_double() -> f64 {
+// Random number generator passed through the render. Each pixel draws from its
+// own stream of the seed, so the image doesn't depend on how pixels are split
+// between threads or the order they are rendered in.
+pub type RenderRng = ChaCha8Rng;
+
+pub fn new_rng(seed: u64, stream: u64) -> RenderRng {
+    let mut rng: RenderRng = RenderRng::seed_from_u64(seed);
+    rng.set_stream(stream);
+    rng
+}
+
+pub fn random_double(rng: &mut RenderRng) -> f64 {
     let between = Uniform::from(0.0..1.0);
-    let mut rng = rand::thread_rng();
-    between.sample(&mut rng)
+    between.sample(rng)
 }
 
-#[allow(dead_code)]
-pub fn random_double_from_range(start: f64, end: f64) -> f64 {
+pub fn random_double_from_range(rng: &mut RenderRng, start: f64, end: f64) -> f64 {
     let between = Uniform::from(start..end);
-    let mut rng = rand::thread_rng();
-    between.sample(&mut rng)
+    between.sample(rng)
+}
+
+// Relative luminance of a linear Rec. 709 color
+pub fn luminance(color: [f64; 3]) -> f64 {
+    0.2126*color[0] + 0.7152*color[1] + 0.0722*color[2]
 }
 
 pub fn clamp(x: f64, min: f64, max: f64) -> f64 {
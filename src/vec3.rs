@@ -1,9 +1,10 @@
-use std::ops::{Neg, AddAssign, MulAssign, DivAssign, Add, Sub, Mul, Div};
+use std::ops::{Neg, AddAssign, MulAssign, DivAssign, Add, Sub, Mul, Div, Index};
 use std::fmt::Display;
+use std::str::FromStr;
 
-use crate::utils::{random_double, random_double_from_range};
+use crate::utils::{RenderRng, random_double, random_double_from_range};
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Vec3 {
     e: [f64; 3],
 }
@@ -22,26 +23,24 @@ impl Vec3 {
     }
 
     #[allow(dead_code)]
-    pub fn new_random() -> Self {
+    pub fn new_random(rng: &mut RenderRng) -> Self {
         Self {
-            e: [random_double(), random_double(), random_double()]
+            e: [random_double(rng), random_double(rng), random_double(rng)]
         }
     }
 
-    pub fn new_random_in_range(min: f64, max: f64) -> Self {
+    pub fn new_random_in_range(rng: &mut RenderRng, min: f64, max: f64) -> Self {
         Self {
             e: [
-                random_double_from_range(min, max),
-                random_double_from_range(min, max),
-                random_double_from_range(min, max)
+                random_double_from_range(rng, min, max),
+                random_double_from_range(rng, min, max),
+                random_double_from_range(rng, min, max)
             ]
         }
     }
 
-    #[allow(dead_code)]
     pub fn x(self) -> f64 { self.e[0] }
     pub fn y(self) -> f64 { self.e[1] }
-    #[allow(dead_code)]
     pub fn z(self) -> f64 { self.e[2] }
 
     pub fn length_squared(self) -> f64 {
@@ -70,6 +69,11 @@ impl Vec3 {
     pub fn unit_vector(self) -> Vec3 {
         self / self.length()
     }
+
+    pub fn near_zero(self) -> bool {
+        let s: f64 = 1e-8;
+        self.e[0].abs() < s && self.e[1].abs() < s && self.e[2].abs() < s
+    }
 }
 
 pub type Point3 = Vec3;
@@ -80,12 +84,58 @@ impl Display for Vec3 {
     }
 }
 
+impl From<[f64; 3]> for Vec3 {
+    fn from(e: [f64; 3]) -> Self {
+        Self {
+            e
+        }
+    }
+}
+
+impl From<Vec3> for [f64; 3] {
+    fn from(v: Vec3) -> Self {
+        v.e
+    }
+}
+
+// Parses vectors written as "x,y,z", which is how they are given on the command line
+impl FromStr for Vec3 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let components: Vec<&str> = s.split(',').map(|c| c.trim()).collect();
+        if components.len() != 3 {
+            return Err(format!("expected three comma separated numbers, got `{}`", s));
+        }
+
+        let mut e: [f64; 3] = [0.0; 3];
+        for (i, component) in components.iter().enumerate() {
+            e[i] = match component.parse::<f64>() {
+                Err(ex) => return Err(format!("invalid number `{}`: {}", component, ex)),
+                Ok(value) => value,
+            };
+        }
+
+        Ok(Self {
+            e
+        })
+    }
+}
+
+impl Index<usize> for Vec3 {
+    type Output = f64;
+
+    fn index(&self, i: usize) -> &f64 {
+        &self.e[i]
+    }
+}
+
 impl Neg for Vec3 {
     type Output = Self;
 
     fn neg(self) -> Self {
         Self {
-            e: [-self.e[0], -self.e[1], self.e[2]]
+            e: [-self.e[0], -self.e[1], -self.e[2]]
         }
     }
 }
@@ -154,6 +204,7 @@ impl Mul for Vec3 {
     }
 }
 
+#[allow(dead_code)]
 pub struct Vec3Multiplier(pub Vec3);
 
 impl Mul<f64> for Vec3Multiplier {
@@ -195,16 +246,16 @@ impl Div<f64> for Vec3 {
 }
 
 //////////////////////////////////////////////////////
-/// Diffuse Methods
-/// 
-/// Allow dead_code so we can swap in and out
-/// whenever we want
+// Diffuse Methods
+//
+// Allow dead_code so we can swap in and out
+// whenever we want
 /////////////////////////////////////////////////////
 
 #[allow(dead_code)]
-pub fn random_in_unit_sphere() -> Vec3 {
+pub fn random_in_unit_sphere(rng: &mut RenderRng) -> Vec3 {
     loop {
-        let p: Vec3 = Vec3::new_random_in_range(-1.0, 1.0);
+        let p: Vec3 = Vec3::new_random_in_range(rng, -1.0, 1.0);
         if p.length_squared() < 1.0 {
             return p;
         }
@@ -212,16 +263,87 @@ pub fn random_in_unit_sphere() -> Vec3 {
 }
 
 #[allow(dead_code)]
-pub fn random_unit_vector() -> Vec3 {
-    random_in_unit_sphere().unit_vector()
+pub fn random_unit_vector(rng: &mut RenderRng) -> Vec3 {
+    random_in_unit_sphere(rng).unit_vector()
 }
 
 #[allow(dead_code)]
-pub fn random_in_hemisphere(normal: &Vec3) -> Vec3 {
-    let in_unit_sphere: Vec3 = random_in_unit_sphere();
+pub fn random_in_hemisphere(rng: &mut RenderRng, normal: &Vec3) -> Vec3 {
+    let in_unit_sphere: Vec3 = random_in_unit_sphere(rng);
     if normal.dot(in_unit_sphere) > 0.0 {
         in_unit_sphere
     } else {
         -in_unit_sphere
     }
+}
+
+// Direction around +Z with a density proportional to the cosine of its angle to
+// +Z, from a point u in the unit square
+pub fn sample_cosine_direction(u: [f64; 2]) -> Vec3 {
+    let phi: f64 = 2.0*std::f64::consts::PI*u[0];
+    let x: f64 = phi.cos()*u[1].sqrt();
+    let y: f64 = phi.sin()*u[1].sqrt();
+    let z: f64 = (1.0 - u[1]).sqrt();
+
+    Vec3::new(x, y, z)
+}
+
+// Uniformly distributed direction from a point u in the unit square
+pub fn sample_unit_sphere(u: [f64; 2]) -> Vec3 {
+    let z: f64 = 1.0 - 2.0*u[0];
+    let r: f64 = (1.0 - z*z).max(0.0).sqrt();
+    let phi: f64 = 2.0*std::f64::consts::PI*u[1];
+
+    Vec3::new(r*phi.cos(), r*phi.sin(), z)
+}
+
+// Uniformly distributed point on the unit disk in the XY plane, from a point u in
+// the unit square. The concentric mapping keeps points that are close in the
+// square close on the disk, so evenly spread samples stay evenly spread.
+pub fn sample_unit_disk(u: [f64; 2]) -> Vec3 {
+    let a: f64 = 2.0*u[0] - 1.0;
+    let b: f64 = 2.0*u[1] - 1.0;
+    if a == 0.0 && b == 0.0 {
+        return Vec3::new_empty();
+    }
+
+    let quarter_pi: f64 = std::f64::consts::FRAC_PI_4;
+    let (r, theta) = if a.abs() > b.abs() {
+        (a, quarter_pi*(b/a))
+    } else {
+        (b, 2.0*quarter_pi - quarter_pi*(a/b))
+    };
+    Vec3::new(r*theta.cos(), r*theta.sin(), 0.0)
+}
+
+//////////////////////////////////////////////////////
+// Light Sampling Methods
+/////////////////////////////////////////////////////
+
+// Uniformly distributed direction inside the cone around +Z covering a sphere of
+// the given radius whose center is at distance_squared from the origin, from a
+// point u in the unit square
+pub fn sample_to_sphere(u: [f64; 2], radius: f64, distance_squared: f64) -> Vec3 {
+    let z: f64 = 1.0 + u[1]*((1.0 - radius*radius/distance_squared).sqrt() - 1.0);
+
+    let phi: f64 = 2.0*std::f64::consts::PI*u[0];
+    let x: f64 = phi.cos()*(1.0 - z*z).sqrt();
+    let y: f64 = phi.sin()*(1.0 - z*z).sqrt();
+
+    Vec3::new(x, y, z)
+}
+
+//////////////////////////////////////////////////////
+// Reflection and Refraction Methods
+/////////////////////////////////////////////////////
+
+pub fn reflect(v: &Vec3, n: &Vec3) -> Vec3 {
+    *v - F64Multiplier(2.0*v.dot(*n)) * *n
+}
+
+pub fn refract(uv: &Vec3, n: &Vec3, etai_over_etat: f64) -> Vec3 {
+    let cos_theta: f64 = (-*uv).dot(*n).min(1.0);
+    let r_out_perp: Vec3 = F64Multiplier(etai_over_etat) * (*uv + F64Multiplier(cos_theta) * *n);
+    let r_out_parallel: Vec3 = F64Multiplier(-(1.0 - r_out_perp.length_squared()).abs().sqrt()) * *n;
+    r_out_perp + r_out_parallel
 }
\ No newline at end of file
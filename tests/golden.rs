@@ -0,0 +1,231 @@
+// Renders the example scenes small, with a fixed seed, and compares them to the
+// golden images in tests/golden. Renders are deterministic, so any change in the
+// output of the integrator, the shapes or the render loop shows up here.
+//
+// After an intended change in output, regenerate the golden images with
+//     UPDATE_GOLDEN=1 cargo test --test golden
+// and check the new images before committing them.
+use std::{env, fs, path::PathBuf};
+
+use image::{ImageBuffer, Rgb, RgbImage};
+
+use rust_ray_tracer::{
+    FrameBuffer,
+    Renderer,
+    RenderSettings,
+    Scene,
+    samplers::sampler::SamplerKind,
+    scene::scene_loader::{load_scene, apply_render_description}};
+
+const IMAGE_WIDTH: u32 = 64;
+const SAMPLES_PER_PIXEL: u32 = 16;
+const SEED: u64 = 1;
+const THREADS: u32 = 2;
+const TILE_SIZE: u32 = 8;
+// Largest root mean square difference allowed between the gamma corrected pixels,
+// with channels in [0, 1]. Leaves room for floating point differences between
+// platforms, a different noise pattern is well above it.
+const TOLERANCE: f64 = 0.01;
+// Differences are scaled up in the diff image so small ones are visible
+const DIFF_SCALE: f64 = 8.0;
+
+fn scene_path(name: &str) -> String {
+    format!("{}/scenes/{}.json", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(format!("{}.png", name))
+}
+
+fn failure_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden")
+}
+
+fn settings(scene: &Scene) -> RenderSettings {
+    let mut settings: RenderSettings = RenderSettings::default();
+    apply_render_description(&mut settings, &scene.render);
+    settings.image_width = IMAGE_WIDTH;
+    settings.samples_per_pixel = SAMPLES_PER_PIXEL;
+    settings.seed = SEED;
+    settings.threads = Some(THREADS);
+    settings.tile_size = TILE_SIZE;
+    settings
+}
+
+fn render(scene: &Scene, settings: &RenderSettings, multithread: bool) -> FrameBuffer {
+    match Renderer::new(scene, *settings).render(multithread) {
+        Err(ex) => panic!("Error rendering: {}", ex),
+        Ok(framebuffer) => framebuffer,
+    }
+}
+
+fn load(name: &str) -> Scene {
+    match load_scene(&scene_path(name)) {
+        Err(ex) => panic!("{}", ex),
+        Ok(scene) => scene,
+    }
+}
+
+fn rmse(actual: &RgbImage, expected: &RgbImage) -> f64 {
+    let mut sum: f64 = 0.0;
+    for (a, e) in actual.pixels().zip(expected.pixels()) {
+        for channel in 0..3 {
+            let difference: f64 = (a[channel] as f64 - e[channel] as f64) / 255.0;
+            sum += difference*difference;
+        }
+    }
+    (sum / (3 * actual.width() * actual.height()) as f64).sqrt()
+}
+
+fn diff_image(actual: &RgbImage, expected: &RgbImage) -> RgbImage {
+    ImageBuffer::from_fn(actual.width(), actual.height(), |x, y| {
+        let a: &Rgb<u8> = actual.get_pixel(x, y);
+        let e: &Rgb<u8> = expected.get_pixel(x, y);
+        Rgb::from([0, 1, 2].map(|channel| {
+            let difference: f64 = (a[channel] as f64 - e[channel] as f64).abs();
+            (difference * DIFF_SCALE).min(255.0) as u8
+        }))
+    })
+}
+
+fn check_golden(name: &str) {
+    check_golden_with_sampler(name, SamplerKind::Independent);
+}
+
+// Golden images for samplers other than the default are named after the scene and the sampler
+fn check_golden_with_sampler(scene_name: &str, sampler: SamplerKind) {
+    let name: String = match sampler {
+        SamplerKind::Independent => scene_name.to_string(),
+        _ => format!("{}-{}", scene_name, sampler),
+    };
+    check_golden_with(scene_name, &name, |settings| settings.sampler = sampler);
+}
+
+// Renders the scene with the test settings changed by adjust and compares it to the golden image called name
+fn check_golden_with(scene_name: &str, name: &str, adjust: impl Fn(&mut RenderSettings)) {
+    let scene: Scene = load(scene_name);
+    let mut settings: RenderSettings = settings(&scene);
+    adjust(&mut settings);
+    let actual: RgbImage = render(&scene, &settings, true).to_rgb_image(&settings);
+    let golden: PathBuf = golden_path(name);
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        if let Some(directory) = golden.parent() {
+            fs::create_dir_all(directory).expect("Error creating the golden image directory");
+        }
+        actual.save(&golden).expect("Error saving the golden image");
+        return;
+    }
+
+    let expected: RgbImage = match image::open(&golden) {
+        Err(ex) => panic!("Error loading golden image {}: {}, regenerate it with UPDATE_GOLDEN=1", golden.display(), ex),
+        Ok(expected) => expected.to_rgb8(),
+    };
+    if actual.dimensions() != expected.dimensions() {
+        panic!("{}: rendered {:?} pixels but the golden image has {:?}", name, actual.dimensions(), expected.dimensions());
+    }
+
+    let error: f64 = rmse(&actual, &expected);
+    println!("{}: rmse {:.5}", name, error);
+    if error > TOLERANCE {
+        let directory: PathBuf = failure_dir();
+        fs::create_dir_all(&directory).expect("Error creating the failure directory");
+        let actual_path: PathBuf = directory.join(format!("{}-actual.png", name));
+        let diff_path: PathBuf = directory.join(format!("{}-diff.png", name));
+        actual.save(&actual_path).expect("Error saving the rendered image");
+        diff_image(&actual, &expected).save(&diff_path).expect("Error saving the diff image");
+
+        panic!("{}: rmse {:.5} is over the tolerance of {}, see {} and {}",
+            name, error, TOLERANCE, actual_path.display(), diff_path.display());
+    }
+}
+
+#[test]
+fn three_spheres() {
+    check_golden("three_spheres");
+}
+
+#[test]
+fn cornell_box() {
+    check_golden("cornell_box");
+}
+
+#[test]
+fn cornell_smoke() {
+    check_golden("cornell_smoke");
+}
+
+#[test]
+fn textures() {
+    check_golden("textures");
+}
+
+#[test]
+fn mesh() {
+    check_golden("mesh");
+}
+
+#[test]
+fn motion_blur() {
+    check_golden("motion_blur");
+}
+
+#[test]
+fn metals() {
+    check_golden("metals");
+}
+
+#[test]
+fn principled() {
+    check_golden("principled");
+}
+
+#[test]
+fn environment_map() {
+    check_golden("environment_map");
+}
+
+#[test]
+fn stratified_sampler() {
+    check_golden_with_sampler("three_spheres", SamplerKind::Stratified);
+}
+
+#[test]
+fn halton_sampler() {
+    check_golden_with_sampler("three_spheres", SamplerKind::Halton);
+}
+
+#[test]
+fn sobol_sampler() {
+    check_golden_with_sampler("three_spheres", SamplerKind::Sobol);
+}
+
+#[test]
+fn blue_noise_sampler() {
+    check_golden_with_sampler("three_spheres", SamplerKind::BlueNoise);
+}
+
+#[test]
+fn adaptive_sampling() {
+    check_golden_with("three_spheres", "three_spheres-adaptive", |settings| {
+        settings.samples_per_pixel = 64;
+        settings.adaptive_threshold = Some(0.05);
+        settings.adaptive_min_samples = 8;
+    });
+}
+
+// The golden images are rendered with several threads, this makes sure a
+// single threaded render gives exactly the same pixels
+#[test]
+fn single_threaded_matches_multithreaded() {
+    let scene: Scene = load("three_spheres");
+    let settings: RenderSettings = settings(&scene);
+
+    let single: FrameBuffer = render(&scene, &settings, false);
+    let multi: FrameBuffer = render(&scene, &settings, true);
+    for y in 0..single.height() {
+        for x in 0..single.width() {
+            assert_eq!(single.get_pixel(x, y), multi.get_pixel(x, y), "pixel ({}, {}) differs", x, y);
+        }
+    }
+}
\ No newline at end of file